@@ -1,3 +1,4 @@
+use crate::features::tasks::BranchNameError;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, TaskError>;
@@ -13,6 +14,8 @@ pub enum TaskError {
     #[error("task is not running")]
     NotRunning,
     #[error(transparent)]
+    BranchName(#[from] BranchNameError),
+    #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Other(#[from] anyhow::Error),
@@ -0,0 +1,227 @@
+use crate::features::tasks::agents::failure::{classify_exit, FailurePhrases};
+use crate::features::tasks::agents::{
+    reject_unsupported, spawn_agent, type_prompt, Agent, AgentCallbacks, AgentStartOptions,
+    StatusTracker,
+};
+use crate::features::tasks::forbidden::{Enforcement, ForbiddenRule};
+use crate::features::tasks::pty::PtyProcess;
+use crate::features::tasks::{AgentFailure, AgentLaunchOptions, ApprovalDecision, TaskStatus};
+use crate::utils::screen::Screen;
+#[cfg(target_os = "windows")]
+use crate::utils::windows::build_wsl_command;
+use anyhow::Context;
+use parking_lot::Mutex;
+#[cfg(not(target_os = "windows"))]
+use portable_pty::CommandBuilder;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ROWS: u16 = 40;
+const DEFAULT_COLS: u16 = 80;
+const CONFIRM_PROMPT: &str = "(Y)es/(N)o";
+/// Only offered when confirming a group of items, such as several files to add.
+const CONFIRM_ALL: &str = "(A)ll";
+const CHAT_HISTORY_FILE: &str = ".aider.chat.history.md";
+/// The litellm errors aider prints when a request fails for good.
+const FAILURE_PHRASES: FailurePhrases = FailurePhrases {
+    not_authenticated: &["litellm.authenticationerror"],
+    rate_limited: &["litellm.ratelimiterror"],
+    network_unreachable: &["litellm.apiconnectionerror"],
+};
+
+#[derive(Clone)]
+pub struct AiderAgent {
+    state: Arc<Mutex<AiderAgentState>>,
+}
+
+struct AiderAgentState {
+    screen: Screen,
+    last_output: Option<Instant>,
+    last_status: Option<TaskStatus>,
+}
+
+impl Default for AiderAgent {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(AiderAgentState {
+                screen: Screen::new(DEFAULT_ROWS as usize, DEFAULT_COLS as usize),
+                last_output: None,
+                last_status: None,
+            })),
+        }
+    }
+}
+
+/// Keeps aider from prompting about housekeeping and leaves committing to the user.
+/// The chat and input histories live in the worktree, so resuming restores the
+/// previous session's chat when there is one.
+fn build_args(worktree_path: &Path, options: &AgentStartOptions) -> anyhow::Result<Vec<String>> {
+    let mut args: Vec<String> = [
+        "--no-auto-commits",
+        "--no-gitignore",
+        "--no-check-update",
+        "--no-show-release-notes",
+        "--analytics-disable",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    if options.resume && worktree_path.join(CHAT_HISTORY_FILE).is_file() {
+        args.push("--restore-chat-history".to_string());
+    }
+    args.extend(launch_args(&options.launch)?);
+    Ok(args)
+}
+
+/// Aider confirms edits and commands itself, so it has no sandbox modes.
+fn launch_args(launch: &AgentLaunchOptions) -> anyhow::Result<Vec<String>> {
+    reject_unsupported(
+        "Aider",
+        &[
+            ("profile", launch.profile.is_some()),
+            ("sandbox", launch.sandbox.is_some()),
+        ],
+    )?;
+    let mut args = Vec::new();
+    if let Some(model) = &launch.model {
+        args.extend(["--model".to_string(), model.clone()]);
+    }
+    if let Some(effort) = launch.effort {
+        args.extend(["--reasoning-effort".to_string(), effort.as_str().to_string()]);
+    }
+    args.extend(launch.extra_args.iter().cloned());
+    Ok(args)
+}
+
+impl StatusTracker for AiderAgent {
+    fn status_from_output(&self, raw: &[u8], timestamp: Instant) -> Option<TaskStatus> {
+        let mut state = self.state.lock();
+        state.last_output = Some(timestamp);
+        state.screen.process(raw);
+        // Answered prompts stay in the scrollback, so only the cursor line counts.
+        let prompt_now = state.screen.cursor_line().contains(CONFIRM_PROMPT);
+        let status = if prompt_now {
+            TaskStatus::AwaitingApproval
+        } else {
+            TaskStatus::Working
+        };
+        let status_changed = state.last_status != Some(status);
+        if status_changed {
+            state.last_status = Some(status);
+        }
+        if status_changed { Some(status) } else { None }
+    }
+
+    fn status_if_idle(&self, now: Instant) -> Option<TaskStatus> {
+        let mut state = self.state.lock();
+        let last = state.last_output?;
+        if now.duration_since(last) >= Duration::from_millis(1000)
+            && state.last_status == Some(TaskStatus::Working)
+        {
+            state.last_status = Some(TaskStatus::Idle);
+            return Some(TaskStatus::Idle);
+        }
+        None
+    }
+}
+
+impl Agent for AiderAgent {
+    fn start(
+        &mut self,
+        worktree_path: &Path,
+        options: &AgentStartOptions,
+        callbacks: AgentCallbacks,
+        rows: u16,
+        cols: u16,
+    ) -> anyhow::Result<PtyProcess> {
+        let args = build_args(worktree_path, options)?;
+        #[cfg(target_os = "windows")]
+        let command = {
+            let arg_refs: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+            build_wsl_command(worktree_path, "aider", &arg_refs)
+        };
+
+        #[cfg(not(target_os = "windows"))]
+        let command = {
+            let mut command = CommandBuilder::new("aider");
+            command.args(&args);
+            command.cwd(worktree_path);
+            command
+        };
+
+        let process = spawn_agent(command, "Aider", rows, cols, callbacks, self.clone())?;
+        // `--message` would exit after one reply, so the prompt is typed in instead.
+        if let Some(prompt) = &options.prompt {
+            type_prompt(&process.writer, prompt).context("failed to send prompt to Aider")?;
+        }
+        Ok(process)
+    }
+
+    fn reset(&mut self, rows: usize, cols: usize) {
+        let mut state = self.state.lock();
+        state.screen = Screen::new(rows, cols);
+        state.last_output = None;
+        state.last_status = None;
+    }
+
+    fn resize(&mut self, rows: usize, cols: usize) {
+        self.state.lock().screen.resize(rows, cols);
+    }
+
+    fn classify_exit(&self, exit_code: i32) -> AgentFailure {
+        classify_exit(&self.state.lock().screen.lines(), exit_code, &FAILURE_PHRASES)
+    }
+
+    /// The question on the cursor line, such as `Add src/main.rs to the chat?`. Shell
+    /// commands are printed above their question, so they are included.
+    fn last_output(&self) -> Option<Instant> {
+        self.state.lock().last_output
+    }
+
+    fn pending_approval(&self) -> Option<String> {
+        let state = self.state.lock();
+        let lines = state.screen.lines();
+        let cursor = state.screen.cursor_row();
+        let (question, _) = lines.get(cursor)?.split_once(CONFIRM_PROMPT)?;
+        let question = question.trim();
+        if !question.starts_with("Run shell command") {
+            return Some(question.to_string());
+        }
+        let mut commands: Vec<&str> = lines[..cursor]
+            .iter()
+            .rev()
+            .map(|line| line.trim())
+            .skip_while(|line| line.is_empty())
+            .take_while(|line| !line.is_empty())
+            .collect();
+        commands.reverse();
+        if commands.is_empty() {
+            return Some(question.to_string());
+        }
+        Some(commands.join("\n"))
+    }
+
+    fn approval_keys(&self, decision: ApprovalDecision) -> Option<String> {
+        let keys = match decision {
+            ApprovalDecision::Approve => "y\r",
+            ApprovalDecision::ApproveAlways => {
+                if !self.state.lock().screen.cursor_line().contains(CONFIRM_ALL) {
+                    return None;
+                }
+                "a\r"
+            }
+            ApprovalDecision::Deny => "n\r",
+        };
+        Some(keys.to_string())
+    }
+
+    /// Aider confirms every shell command, and illuc denies the forbidden ones itself.
+    fn enforce(&self, _rules: &[ForbiddenRule]) -> Enforcement {
+        Enforcement::default()
+    }
+
+    fn launch_args(&self, launch: &AgentLaunchOptions) -> anyhow::Result<Vec<String>> {
+        launch_args(launch)
+    }
+}
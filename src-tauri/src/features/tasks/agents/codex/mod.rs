@@ -1,23 +1,43 @@
-use crate::features::tasks::agents::{Agent, AgentCallbacks, AgentRuntime, ChildHandle};
-use crate::features::tasks::TaskStatus;
+use crate::features::tasks::agents::failure::{classify_exit, FailurePhrases};
+use crate::features::tasks::agents::{
+    dialog_text, spawn_agent, Agent, AgentCallbacks, AgentStartOptions, StatusTracker,
+};
+use crate::features::tasks::agents::sessions::{
+    parse_timestamp, resolve_agent_home_dir, resolve_session_cwd, sort_sessions,
+};
+use crate::features::tasks::forbidden::{Enforcement, ForbiddenKind, ForbiddenRule};
+use crate::features::tasks::models::AgentSession;
+use crate::features::tasks::pty::PtyProcess;
+use crate::features::tasks::{
+    AgentFailure, AgentLaunchOptions, ApprovalDecision, SandboxMode, TaskStatus,
+};
 use crate::utils::screen::Screen;
 #[cfg(target_os = "windows")]
 use crate::utils::windows::build_wsl_command;
-use anyhow::Context;
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
-use portable_pty::{native_pty_system, PtySize};
 #[cfg(not(target_os = "windows"))]
 use portable_pty::CommandBuilder;
-use std::io::Read;
-use std::path::Path;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 
 const DEFAULT_ROWS: u16 = 40;
 const DEFAULT_COLS: u16 = 80;
 const APPROVAL_PROMPT: &str = "would you like to run the following command";
+const CODEX_SESSION_DIR: &str = ".codex/sessions";
+/// Codex's own messages when a request fails for good.
+const FAILURE_PHRASES: FailurePhrases = FailurePhrases {
+    not_authenticated: &["codex login", "not logged in", "unexpected status 401"],
+    rate_limited: &["usage limit", "last status: 429", "unexpected status 429"],
+    network_unreachable: &[
+        "stream disconnected before completion",
+        "error sending request for url",
+    ],
+};
 
 #[derive(Clone)]
 pub struct CodexAgent {
@@ -42,7 +62,189 @@ impl Default for CodexAgent {
     }
 }
 
-impl CodexAgent {
+/// Rollouts live under `$CODEX_HOME/sessions/YYYY/MM/DD/rollout-*.jsonl`.
+pub fn sessions_dir(worktree_path: &Path) -> anyhow::Result<PathBuf> {
+    #[cfg(not(target_os = "windows"))]
+    if let Some(codex_home) = std::env::var_os("CODEX_HOME").filter(|value| !value.is_empty()) {
+        return Ok(PathBuf::from(codex_home).join("sessions"));
+    }
+    Ok(resolve_agent_home_dir(worktree_path)?.join(CODEX_SESSION_DIR))
+}
+
+fn collect_rollout_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_rollout_files(&path, files);
+        } else if file_type.is_file() && is_rollout_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn is_rollout_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("rollout-") && name.ends_with(".jsonl"))
+}
+
+/// Reads the `session_meta` record Codex writes as the first line of a rollout, if the
+/// rollout belongs to `desired_cwd`.
+pub fn read_session(path: &Path, desired_cwd: &str) -> Option<AgentSession> {
+    if !is_rollout_file(path) {
+        return None;
+    }
+    let file = fs::File::open(path).ok()?;
+    let mut first_line = String::new();
+    BufReader::new(file).read_line(&mut first_line).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&first_line).ok()?;
+    if value.get("type").and_then(|value| value.as_str()) != Some("session_meta") {
+        return None;
+    }
+    let payload = value.get("payload")?;
+    let cwd = payload.get("cwd").and_then(|value| value.as_str())?;
+    if !same_cwd(cwd, desired_cwd) {
+        return None;
+    }
+    let session_id = payload.get("id").and_then(|value| value.as_str())?.to_string();
+    let started_at = payload
+        .get("timestamp")
+        .or_else(|| value.get("timestamp"))
+        .and_then(|value| value.as_str())
+        .and_then(parse_timestamp);
+    let updated_at = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+    Some(AgentSession {
+        session_id,
+        started_at,
+        updated_at,
+        path: path.to_path_buf(),
+    })
+}
+
+/// Codex records the cwd it was launched in, which may still contain symlinks.
+fn same_cwd(recorded: &str, desired_cwd: &str) -> bool {
+    let recorded = recorded.trim_end_matches('/');
+    if recorded == desired_cwd.trim_end_matches('/') {
+        return true;
+    }
+    #[cfg(not(target_os = "windows"))]
+    if let Ok(canonical) = fs::canonicalize(recorded) {
+        return canonical.to_string_lossy() == desired_cwd;
+    }
+    false
+}
+
+/// Codex sessions recorded for `worktree_path`, most recent first.
+pub fn list_sessions(worktree_path: &Path) -> anyhow::Result<Vec<AgentSession>> {
+    let desired_cwd = resolve_session_cwd(worktree_path)?;
+    let mut files = Vec::new();
+    collect_rollout_files(&sessions_dir(worktree_path)?, &mut files);
+    let mut sessions: Vec<AgentSession> = files
+        .iter()
+        .filter_map(|path| read_session(path, &desired_cwd))
+        .collect();
+    sort_sessions(&mut sessions);
+    Ok(sessions)
+}
+
+/// A new task starts a fresh session; later runs resume the requested session or the
+/// worktree's latest one. `resume --last` is avoided because it is not scoped to the
+/// worktree.
+fn build_args(worktree_path: &Path, options: &AgentStartOptions) -> anyhow::Result<Vec<String>> {
+    let mut args = launch_args(&options.launch);
+    args.extend(enforcement(&options.forbidden).args);
+    let session_id = match &options.session_id {
+        Some(session_id) => Some(session_id.clone()),
+        None if options.resume => list_sessions(worktree_path)?
+            .into_iter()
+            .next()
+            .map(|session| session.session_id),
+        None => None,
+    };
+    if let Some(session_id) = session_id {
+        args.extend(["resume".to_string(), session_id]);
+    }
+    if let Some(prompt) = &options.prompt {
+        args.extend(["--".to_string(), prompt.clone()]);
+    }
+    Ok(args)
+}
+
+/// Runs with `--full-auto` unless another sandbox mode is asked for.
+fn launch_args(launch: &AgentLaunchOptions) -> Vec<String> {
+    let sandbox: &[&str] = match launch.sandbox.unwrap_or(SandboxMode::FullAuto) {
+        SandboxMode::ReadOnly => &["--sandbox", "read-only", "--ask-for-approval", "on-request"],
+        SandboxMode::WorkspaceWrite => {
+            &["--sandbox", "workspace-write", "--ask-for-approval", "untrusted"]
+        }
+        SandboxMode::FullAuto => &["--full-auto"],
+    };
+    let mut args: Vec<String> = sandbox.iter().map(|arg| arg.to_string()).collect();
+    if let Some(model) = &launch.model {
+        args.extend(["-m".to_string(), model.clone()]);
+    }
+    if let Some(effort) = launch.effort {
+        args.extend(["-c".to_string(), format!("model_reasoning_effort={}", effort.as_str())]);
+    }
+    if let Some(profile) = &launch.profile {
+        args.extend(["-p".to_string(), profile.clone()]);
+    }
+    args.extend(launch.extra_args.iter().cloned());
+    args
+}
+
+/// Every sandbox mode refuses deletes outside the worktree, and with network access off
+/// pushes and publishes cannot reach their remote. Codex has no way to refuse any other
+/// command.
+fn enforcement(rules: &[ForbiddenRule]) -> Enforcement {
+    let mut enforcement = Enforcement::default();
+    let mut offline = false;
+    for rule in rules {
+        match &rule.kind {
+            ForbiddenKind::Command { network: true, .. } => offline = true,
+            ForbiddenKind::DeleteOutsideWorktree => {}
+            ForbiddenKind::Command { .. } => enforcement.unenforced.push(rule.label.clone()),
+        }
+    }
+    if offline {
+        enforcement.args.extend([
+            "-c".to_string(),
+            "sandbox_workspace_write.network_access=false".to_string(),
+        ]);
+    }
+    enforcement
+}
+
+/// The dialog shows the command as `$ <command>` below the question, followed by the
+/// numbered answers.
+fn approval_command(screen_text: &str) -> Option<String> {
+    let mut lines = screen_text
+        .lines()
+        .skip_while(|line| !line.contains(APPROVAL_PROMPT))
+        .skip(1)
+        .map(dialog_text);
+    let first = lines.find_map(|line| line.strip_prefix("$ "))?;
+    let mut command = vec![first];
+    command.extend(lines.take_while(|line| !line.is_empty() && !is_answer(line)));
+    Some(command.join("\n"))
+}
+
+fn is_answer(line: &str) -> bool {
+    line.split_once(". ").is_some_and(|(number, _)| {
+        !number.is_empty() && number.chars().all(|ch| ch.is_ascii_digit())
+    })
+}
+
+impl StatusTracker for CodexAgent {
     fn status_from_output(&self, raw: &[u8], timestamp: Instant) -> Option<TaskStatus> {
         let mut state = self.state.lock();
         state.last_output = Some(timestamp);
@@ -80,113 +282,28 @@ impl Agent for CodexAgent {
     fn start(
         &mut self,
         worktree_path: &Path,
+        options: &AgentStartOptions,
         callbacks: AgentCallbacks,
         rows: u16,
         cols: u16,
-    ) -> anyhow::Result<AgentRuntime> {
-        let pty_system = native_pty_system();
-        let rows = rows.max(1);
-        let cols = cols.max(1);
-        let pair = pty_system.openpty(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })?;
-
-        let master = pair.master;
-        let writer = master
-            .take_writer()
-            .context("failed to obtain pty writer")?;
-        let reader = master
-            .try_clone_reader()
-            .context("failed to clone pty reader")?;
-        let master = Arc::new(Mutex::new(master));
-        let writer = Arc::new(Mutex::new(writer));
-
+    ) -> anyhow::Result<PtyProcess> {
+        let args = build_args(worktree_path, options)?;
         #[cfg(target_os = "windows")]
-        let command = build_wsl_command(
-            worktree_path,
-            "codex",
-            &["--enable", "tui2", "--full-auto", "resume", "--last"],
-        );
+        let command = {
+            let mut arg_refs = vec!["--enable", "tui2"];
+            arg_refs.extend(args.iter().map(|arg| arg.as_str()));
+            build_wsl_command(worktree_path, "codex", &arg_refs)
+        };
 
         #[cfg(not(target_os = "windows"))]
         let command = {
             let mut command = CommandBuilder::new("codex");
-            command.args(["--full-auto", "resume", "--last"]);
+            command.args(&args);
             command.cwd(worktree_path);
             command
         };
 
-        let child = pair
-            .slave
-            .spawn_command(command)
-            .context("failed to start Codex")?;
-        let child: Arc<Mutex<ChildHandle>> = Arc::new(Mutex::new(child));
-        let status_handle = self.clone();
-        let output_callbacks = callbacks.clone();
-        let running = Arc::new(AtomicBool::new(true));
-        let idle_running = Arc::clone(&running);
-        let idle_handle = self.clone();
-        let idle_callbacks = callbacks.clone();
-        std::thread::spawn(move || {
-            while idle_running.load(Ordering::Relaxed) {
-                std::thread::sleep(Duration::from_millis(250));
-                if let Some(status) = idle_handle.status_if_idle(Instant::now()) {
-                    (idle_callbacks.on_status)(status);
-                }
-            }
-        });
-
-        std::thread::spawn(move || {
-            let mut reader = reader;
-            let mut buffer = [0u8; 8192];
-            loop {
-                match reader.read(&mut buffer) {
-                    Ok(0) => break,
-                    Ok(size) => {
-                        let now = Instant::now();
-                        let chunk = String::from_utf8_lossy(&buffer[..size]).to_string();
-                        if let Some(status) =
-                            status_handle.status_from_output(&buffer[..size], now)
-                        {
-                            (output_callbacks.on_status)(status);
-                        }
-                        (output_callbacks.on_output)(chunk);
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
-
-        let exit_callbacks = callbacks.clone();
-        let exit_child = child.clone();
-        let exit_running = Arc::clone(&running);
-        std::thread::spawn(move || {
-            let exit_code = loop {
-                {
-                    let mut child_guard = exit_child.lock();
-                    match child_guard.try_wait() {
-                        Ok(Some(status)) => {
-                            let code = status.exit_code() as i32;
-                            break if status.success() { 0 } else { code };
-                        }
-                        Ok(None) => {}
-                        Err(_) => break 1,
-                    }
-                }
-                std::thread::sleep(Duration::from_millis(200));
-            };
-            exit_running.store(false, Ordering::Relaxed);
-            (exit_callbacks.on_exit)(exit_code);
-        });
-
-        Ok(AgentRuntime {
-            child,
-            writer,
-            master,
-        })
+        spawn_agent(command, "Codex", rows, cols, callbacks, self.clone())
     }
 
     fn reset(&mut self, rows: usize, cols: usize) {
@@ -200,4 +317,32 @@ impl Agent for CodexAgent {
         self.state.lock().screen.resize(rows, cols);
     }
 
+    fn classify_exit(&self, exit_code: i32) -> AgentFailure {
+        classify_exit(&self.state.lock().screen.lines(), exit_code, &FAILURE_PHRASES)
+    }
+
+    fn last_output(&self) -> Option<Instant> {
+        self.state.lock().last_output
+    }
+
+    fn pending_approval(&self) -> Option<String> {
+        approval_command(&self.state.lock().screen.full_text())
+    }
+
+    fn approval_keys(&self, decision: ApprovalDecision) -> Option<String> {
+        let keys = match decision {
+            ApprovalDecision::Approve => "y",
+            ApprovalDecision::ApproveAlways => "a",
+            ApprovalDecision::Deny => "\x1b",
+        };
+        Some(keys.to_string())
+    }
+
+    fn enforce(&self, rules: &[ForbiddenRule]) -> Enforcement {
+        enforcement(rules)
+    }
+
+    fn launch_args(&self, launch: &AgentLaunchOptions) -> anyhow::Result<Vec<String>> {
+        Ok(launch_args(launch))
+    }
 }
@@ -1,30 +1,44 @@
-use crate::features::tasks::agents::{Agent, AgentCallbacks, AgentRuntime, ChildHandle};
-use crate::features::tasks::TaskStatus;
+use crate::features::tasks::agents::failure::{classify_exit, FailurePhrases};
+use crate::features::tasks::agents::{
+    dialog_text, reject_unsupported, spawn_agent, Agent, AgentCallbacks, AgentStartOptions,
+    StatusTracker,
+};
+use crate::features::tasks::agents::sessions::{
+    parse_timestamp, resolve_agent_home_dir, resolve_session_cwd, sort_sessions,
+};
+use crate::features::tasks::forbidden::{Enforcement, ForbiddenKind, ForbiddenRule};
+use crate::features::tasks::models::AgentSession;
+use crate::features::tasks::pty::PtyProcess;
+use crate::features::tasks::{
+    AgentFailure, AgentLaunchOptions, ApprovalDecision, SandboxMode, TaskStatus,
+};
 use crate::utils::screen::Screen;
 #[cfg(target_os = "windows")]
 use crate::utils::windows::build_wsl_command;
-#[cfg(target_os = "windows")]
-use crate::utils::windows::build_wsl_process_command;
-#[cfg(target_os = "windows")]
-use crate::utils::windows::to_wsl_path;
-use anyhow::Context;
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
-use portable_pty::{native_pty_system, PtySize};
 #[cfg(not(target_os = "windows"))]
 use portable_pty::CommandBuilder;
 use std::fs;
-use std::io::Read;
-use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 const DEFAULT_ROWS: u16 = 40;
 const DEFAULT_COLS: u16 = 80;
 
+/// Every permission dialog offers this way to decline, whatever it asks about.
+const APPROVAL_PROMPT: &str = "tell Copilot what to do differently";
+const APPROVAL_QUESTION: &str = "Do you want to";
+
 const COPILOT_SESSION_DIR: &str = ".copilot/session-state";
 const COPILOT_LEGACY_SESSION_DIR: &str = ".copilot/history-session-state";
+/// Copilot's own messages when a request fails for good.
+const FAILURE_PHRASES: FailurePhrases = FailurePhrases {
+    not_authenticated: &["no authentication information found", "use /login", "run /login"],
+    rate_limited: &["rate limit exceeded", "429 too many requests"],
+    network_unreachable: &["getaddrinfo enotfound", "connect econnrefused", "fetch failed"],
+};
 
 #[derive(Clone)]
 pub struct CopilotAgent {
@@ -49,69 +63,20 @@ impl Default for CopilotAgent {
     }
 }
 
-struct SessionCandidate {
-    session_id: String,
-    timestamp: Option<DateTime<Utc>>,
-}
-
-fn resolve_session_cwd(worktree_path: &Path) -> anyhow::Result<String> {
-    let canonical = fs::canonicalize(worktree_path)
-        .with_context(|| format!("failed to resolve cwd {}", worktree_path.display()))?;
-    #[cfg(target_os = "windows")]
-    if let Some(wsl_path) = to_wsl_path(&canonical) {
-        return Ok(wsl_path);
-    }
-    Ok(canonical.to_string_lossy().to_string())
-}
-
-#[cfg(not(target_os = "windows"))]
-fn resolve_home_dir() -> anyhow::Result<std::path::PathBuf> {
-    std::env::var_os("HOME")
-        .or_else(|| std::env::var_os("USERPROFILE"))
-        .map(std::path::PathBuf::from)
-        .context("failed to resolve home directory")
+/// Sessions the current Copilot CLI writes; older releases used a legacy directory.
+pub fn sessions_dir(worktree_path: &Path) -> anyhow::Result<PathBuf> {
+    Ok(resolve_agent_home_dir(worktree_path)?.join(COPILOT_SESSION_DIR))
 }
 
-#[cfg(target_os = "windows")]
-fn resolve_wsl_home_dir(worktree_path: &Path) -> anyhow::Result<std::path::PathBuf> {
-    let output = build_wsl_process_command(
-        worktree_path,
-        "bash",
-        &["-lc", "wslpath -w \"$HOME\""],
-    )
-    .output()
-    .context("failed to query WSL home directory")?;
-    if !output.status.success() {
-        return Err(anyhow::anyhow!("failed to query WSL home directory"));
-    }
-    let home = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if home.is_empty() {
-        return Err(anyhow::anyhow!("WSL home directory is empty"));
-    }
-    Ok(std::path::PathBuf::from(home))
-}
-
-fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
-    let mut normalized = value.trim().to_string();
-    if normalized.ends_with('Z') {
-        normalized = format!("{}+00:00", normalized.trim_end_matches('Z'));
-    }
-    if let Ok(parsed) = DateTime::parse_from_rfc3339(&normalized) {
-        return Some(parsed.with_timezone(&Utc));
-    }
-    if let Ok(naive) = NaiveDateTime::parse_from_str(&normalized, "%Y-%m-%dT%H:%M:%S%.f") {
-        return Some(Utc.from_utc_datetime(&naive));
-    }
-    None
-}
-
-fn parse_session_file(path: &Path, desired_cwd: &str) -> Option<SessionCandidate> {
+/// Reads a session log, if it was recorded in `desired_cwd`.
+pub fn read_session(path: &Path, desired_cwd: &str) -> Option<AgentSession> {
     let data = fs::read_to_string(path).ok()?;
     if !data.contains(desired_cwd) {
         return None;
     }
 
     let mut session_id: Option<String> = None;
+    let mut earliest_timestamp: Option<DateTime<Utc>> = None;
     let mut latest_timestamp: Option<DateTime<Utc>> = None;
 
     for line in data.lines() {
@@ -135,6 +100,10 @@ fn parse_session_file(path: &Path, desired_cwd: &str) -> Option<SessionCandidate
             .and_then(|value| value.as_str())
             .and_then(parse_timestamp)
         {
+            earliest_timestamp = match earliest_timestamp {
+                Some(current) if current <= ts => Some(current),
+                _ => Some(ts),
+            };
             latest_timestamp = match latest_timestamp {
                 Some(current) if current >= ts => Some(current),
                 _ => Some(ts),
@@ -148,63 +117,87 @@ fn parse_session_file(path: &Path, desired_cwd: &str) -> Option<SessionCandidate
             .map(|value| value.to_string())
     })?;
 
-    let timestamp = latest_timestamp;
-    Some(SessionCandidate {
+    Some(AgentSession {
         session_id,
-        timestamp,
+        started_at: earliest_timestamp,
+        updated_at: latest_timestamp,
+        path: path.to_path_buf(),
     })
 }
 
-fn find_latest_session_in_dir(dir: &Path, desired_cwd: &str) -> Option<String> {
-    let entries = fs::read_dir(dir).ok()?;
-    let mut best: Option<SessionCandidate> = None;
+fn collect_sessions_in_dir(dir: &Path, desired_cwd: &str, sessions: &mut Vec<AgentSession>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
     for entry in entries.flatten() {
         let path = entry.path();
         if entry.file_type().map(|ty| ty.is_file()).unwrap_or(false) {
-            if let Some(candidate) = parse_session_file(&path, desired_cwd) {
-                let replace = match (&candidate.timestamp, &best) {
-                    (Some(candidate_ts), Some(best)) => match best.timestamp {
-                        Some(best_ts) => candidate_ts > &best_ts,
-                        None => true,
-                    },
-                    (Some(_), None) => true,
-                    (None, Some(_)) => false,
-                    (None, None) => true,
-                };
-                if replace {
-                    best = Some(candidate);
+            if let Some(session) = read_session(&path, desired_cwd) {
+                if sessions
+                    .iter()
+                    .all(|existing| existing.session_id != session.session_id)
+                {
+                    sessions.push(session);
                 }
             }
         }
     }
-    best.map(|candidate| candidate.session_id)
 }
 
-fn find_latest_session_id(worktree_path: &Path) -> anyhow::Result<Option<String>> {
+/// Copilot sessions recorded for `worktree_path`, most recent first.
+pub fn list_sessions(worktree_path: &Path) -> anyhow::Result<Vec<AgentSession>> {
     let desired_cwd = resolve_session_cwd(worktree_path)?;
-    #[cfg(target_os = "windows")]
-    let home_dir = resolve_wsl_home_dir(worktree_path)?;
-    #[cfg(not(target_os = "windows"))]
-    let home_dir = resolve_home_dir()?;
-    let primary = home_dir.join(COPILOT_SESSION_DIR);
-    let legacy = home_dir.join(COPILOT_LEGACY_SESSION_DIR);
-
-    if let Some(session_id) = find_latest_session_in_dir(&primary, &desired_cwd) {
-        return Ok(Some(session_id));
-    }
-    if let Some(session_id) = find_latest_session_in_dir(&legacy, &desired_cwd) {
-        return Ok(Some(session_id));
-    }
+    let home_dir = resolve_agent_home_dir(worktree_path)?;
+    let mut sessions = Vec::new();
+    collect_sessions_in_dir(&home_dir.join(COPILOT_SESSION_DIR), &desired_cwd, &mut sessions);
+    collect_sessions_in_dir(
+        &home_dir.join(COPILOT_LEGACY_SESSION_DIR),
+        &desired_cwd,
+        &mut sessions,
+    );
+    sort_sessions(&mut sessions);
+    Ok(sessions)
+}
 
-    Ok(None)
+/// The dialog box holds a title and the command or path above its question. Shell
+/// commands are shown as `$ <command>`; other requests are reported as the box reads.
+fn approval_subject(screen_text: &str) -> Option<String> {
+    let lines: Vec<&str> = screen_text.lines().collect();
+    let question = lines
+        .iter()
+        .rposition(|line| line.contains(APPROVAL_QUESTION))?;
+    let start = lines[..question]
+        .iter()
+        .rposition(|line| line.trim_start().starts_with('╭'))
+        .map_or(0, |top| top + 1);
+    let body: Vec<&str> = lines[start..question]
+        .iter()
+        .map(|line| dialog_text(line))
+        .filter(|line| !line.is_empty())
+        .collect();
+    let commands: Vec<&str> = body
+        .iter()
+        .filter_map(|line| line.strip_prefix("$ "))
+        .collect();
+    let subject = if commands.is_empty() { body } else { commands };
+    if subject.is_empty() {
+        return Some(dialog_text(lines[question]).to_string());
+    }
+    Some(subject.join("\n"))
 }
 
-impl CopilotAgent {
+impl StatusTracker for CopilotAgent {
     fn status_from_output(&self, raw: &[u8], timestamp: Instant) -> Option<TaskStatus> {
         let mut state = self.state.lock();
         state.last_output = Some(timestamp);
         state.screen.process(raw);
-        let status = TaskStatus::Working;
+        // The dialog is redrawn away once answered, so only the visible screen counts.
+        let prompt_now = state.screen.full_text().contains(APPROVAL_PROMPT);
+        let status = if prompt_now {
+            TaskStatus::AwaitingApproval
+        } else {
+            TaskStatus::Working
+        };
         let status_changed = state.last_status != Some(status);
         if status_changed {
             state.last_status = Some(status);
@@ -229,40 +222,29 @@ impl Agent for CopilotAgent {
     fn start(
         &mut self,
         worktree_path: &Path,
+        options: &AgentStartOptions,
         callbacks: AgentCallbacks,
         rows: u16,
         cols: u16,
-    ) -> anyhow::Result<AgentRuntime> {
-        let pty_system = native_pty_system();
-        let rows = rows.max(1);
-        let cols = cols.max(1);
-        let maybe_session_id = find_latest_session_id(worktree_path)?;
-        let mut args = vec![
-            "--allow-all-tools".to_string(),
-            "--deny-tool".to_string(),
-            "shell(git push)".to_string(),
-        ];
+    ) -> anyhow::Result<PtyProcess> {
+        let maybe_session_id = match &options.session_id {
+            Some(session_id) => Some(session_id.clone()),
+            None if options.resume => list_sessions(worktree_path)?
+                .into_iter()
+                .next()
+                .map(|session| session.session_id),
+            None => None,
+        };
+        let mut args = self.launch_args(&options.launch)?;
+        args.extend(self.enforce(&options.forbidden).args);
         if let Some(session_id) = maybe_session_id {
             args.push("--resume".to_string());
             args.push(session_id);
         }
-        let pair = pty_system.openpty(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })?;
-
-        let master = pair.master;
-        let writer = master
-            .take_writer()
-            .context("failed to obtain pty writer")?;
-        let reader = master
-            .try_clone_reader()
-            .context("failed to clone pty reader")?;
-        let master = Arc::new(Mutex::new(master));
-        let writer = Arc::new(Mutex::new(writer));
-
+        if let Some(prompt) = &options.prompt {
+            args.push("--interactive".to_string());
+            args.push(prompt.clone());
+        }
         #[cfg(target_os = "windows")]
         let command = {
             let arg_refs: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
@@ -277,75 +259,7 @@ impl Agent for CopilotAgent {
             command
         };
 
-        let child = pair
-            .slave
-            .spawn_command(command)
-            .context("failed to start Copilot")?;
-        let child: Arc<Mutex<ChildHandle>> = Arc::new(Mutex::new(child));
-
-        let status_handle = self.clone();
-        let output_callbacks = callbacks.clone();
-        let running = Arc::new(AtomicBool::new(true));
-        let idle_running = Arc::clone(&running);
-        let idle_handle = self.clone();
-        let idle_callbacks = callbacks.clone();
-        std::thread::spawn(move || {
-            while idle_running.load(Ordering::Relaxed) {
-                std::thread::sleep(Duration::from_millis(250));
-                if let Some(status) = idle_handle.status_if_idle(Instant::now()) {
-                    (idle_callbacks.on_status)(status);
-                }
-            }
-        });
-
-        std::thread::spawn(move || {
-            let mut reader = reader;
-            let mut buffer = [0u8; 8192];
-            loop {
-                match reader.read(&mut buffer) {
-                    Ok(0) => break,
-                    Ok(size) => {
-                        let now = Instant::now();
-                        let chunk = String::from_utf8_lossy(&buffer[..size]).to_string();
-                        if let Some(status) =
-                            status_handle.status_from_output(&buffer[..size], now)
-                        {
-                            (output_callbacks.on_status)(status);
-                        }
-                        (output_callbacks.on_output)(chunk);
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
-
-        let exit_callbacks = callbacks.clone();
-        let exit_child = child.clone();
-        let exit_running = Arc::clone(&running);
-        std::thread::spawn(move || {
-            let exit_code = loop {
-                {
-                    let mut child_guard = exit_child.lock();
-                    match child_guard.try_wait() {
-                        Ok(Some(status)) => {
-                            let code = status.exit_code() as i32;
-                            break if status.success() { 0 } else { code };
-                        }
-                        Ok(None) => {}
-                        Err(_) => break 1,
-                    }
-                }
-                std::thread::sleep(Duration::from_millis(200));
-            };
-            exit_running.store(false, Ordering::Relaxed);
-            (exit_callbacks.on_exit)(exit_code);
-        });
-
-        Ok(AgentRuntime {
-            child,
-            writer,
-            master,
-        })
+        spawn_agent(command, "Copilot", rows, cols, callbacks, self.clone())
     }
 
     fn reset(&mut self, rows: usize, cols: usize) {
@@ -358,4 +272,94 @@ impl Agent for CopilotAgent {
     fn resize(&mut self, rows: usize, cols: usize) {
         self.state.lock().screen.resize(rows, cols);
     }
+
+    fn classify_exit(&self, exit_code: i32) -> AgentFailure {
+        classify_exit(&self.state.lock().screen.lines(), exit_code, &FAILURE_PHRASES)
+    }
+
+    fn last_output(&self) -> Option<Instant> {
+        self.state.lock().last_output
+    }
+
+    fn pending_approval(&self) -> Option<String> {
+        approval_subject(&self.state.lock().screen.full_text())
+    }
+
+    /// Answers are picked by their number in the dialog; Esc declines.
+    fn approval_keys(&self, decision: ApprovalDecision) -> Option<String> {
+        let keys = match decision {
+            ApprovalDecision::Approve => "1",
+            ApprovalDecision::ApproveAlways => "2",
+            ApprovalDecision::Deny => "\x1b",
+        };
+        Some(keys.to_string())
+    }
+
+    /// `--deny-tool shell(...)` matches a command and at most one subcommand, so rules
+    /// with flags, such as `git reset --hard`, and path checks cannot be expressed.
+    fn enforce(&self, rules: &[ForbiddenRule]) -> Enforcement {
+        let mut enforcement = Enforcement::default();
+        for rule in rules {
+            match &rule.kind {
+                ForbiddenKind::Command { words, .. }
+                    if words.len() <= 2 && !words.iter().any(|word| word.starts_with('-')) =>
+                {
+                    enforcement.args.push("--deny-tool".to_string());
+                    enforcement.args.push(format!("shell({})", words.join(" ")));
+                }
+                _ => enforcement.unenforced.push(rule.label.clone()),
+            }
+        }
+        enforcement
+    }
+
+    /// Sandbox modes map onto tool permissions: `write` covers file edits, and only
+    /// full-auto runs shell commands without asking.
+    fn launch_args(&self, launch: &AgentLaunchOptions) -> anyhow::Result<Vec<String>> {
+        reject_unsupported(
+            "Copilot CLI",
+            &[
+                ("effort", launch.effort.is_some()),
+                ("profile", launch.profile.is_some()),
+            ],
+        )?;
+        let sandbox: &[&str] = match launch.sandbox.unwrap_or(SandboxMode::FullAuto) {
+            SandboxMode::ReadOnly => &["--deny-tool", "write"],
+            SandboxMode::WorkspaceWrite => &["--allow-tool", "write"],
+            SandboxMode::FullAuto => &["--allow-all-tools"],
+        };
+        let mut args: Vec<String> = sandbox.iter().map(|arg| arg.to_string()).collect();
+        if let Some(model) = &launch.model {
+            args.extend(["--model".to_string(), model.clone()]);
+        }
+        args.extend(launch.extra_args.iter().cloned());
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKING: &[u8] = include_bytes!("fixtures/working.ansi");
+    const DIALOG: &[u8] = include_bytes!("fixtures/dialog.ansi");
+    const ANSWERED: &[u8] = include_bytes!("fixtures/answered.ansi");
+
+    #[test]
+    fn permission_dialog_awaits_approval_until_answered() {
+        let agent = CopilotAgent::default();
+        let now = Instant::now();
+
+        assert_eq!(agent.status_from_output(WORKING, now), Some(TaskStatus::Working));
+        assert_eq!(agent.pending_approval(), None);
+
+        assert_eq!(
+            agent.status_from_output(DIALOG, now),
+            Some(TaskStatus::AwaitingApproval)
+        );
+        assert_eq!(agent.pending_approval().as_deref(), Some("cargo test --workspace"));
+
+        assert_eq!(agent.status_from_output(ANSWERED, now), Some(TaskStatus::Working));
+        assert_eq!(agent.pending_approval(), None);
+    }
 }
@@ -0,0 +1,232 @@
+use crate::features::tasks::agents::failure::{classify_exit, FailurePhrases};
+use crate::features::tasks::agents::{
+    reject_unsupported, spawn_agent, type_prompt, Agent, AgentCallbacks, AgentStartOptions,
+    StatusTracker,
+};
+use crate::features::tasks::config::AgentDefinition;
+use crate::features::tasks::forbidden::{Enforcement, ForbiddenKind, ForbiddenRule};
+use crate::features::tasks::pty::PtyProcess;
+use crate::features::tasks::{AgentFailure, AgentLaunchOptions, ApprovalDecision, TaskStatus};
+use crate::utils::screen::Screen;
+#[cfg(target_os = "windows")]
+use crate::utils::windows::build_wsl_command;
+use anyhow::Context;
+use parking_lot::Mutex;
+#[cfg(not(target_os = "windows"))]
+use portable_pty::CommandBuilder;
+use regex::Regex;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ROWS: u16 = 40;
+const DEFAULT_COLS: u16 = 80;
+
+/// Runs an agent described in config, detecting its state with the configured regexes.
+#[derive(Clone)]
+pub struct CustomAgent {
+    definition: Arc<AgentDefinition>,
+    approval_pattern: Option<Regex>,
+    idle_pattern: Option<Regex>,
+    state: Arc<Mutex<CustomAgentState>>,
+}
+
+struct CustomAgentState {
+    screen: Screen,
+    last_output: Option<Instant>,
+    last_status: Option<TaskStatus>,
+}
+
+impl CustomAgent {
+    pub fn new(definition: AgentDefinition) -> anyhow::Result<Self> {
+        let approval_pattern = compile_pattern(&definition.id, &definition.approval_pattern)?;
+        let idle_pattern = compile_pattern(&definition.id, &definition.idle_pattern)?;
+        Ok(Self {
+            definition: Arc::new(definition),
+            approval_pattern,
+            idle_pattern,
+            state: Arc::new(Mutex::new(CustomAgentState {
+                screen: Screen::new(DEFAULT_ROWS as usize, DEFAULT_COLS as usize),
+                last_output: None,
+                last_status: None,
+            })),
+        })
+    }
+
+    pub fn label(&self) -> String {
+        self.definition
+            .name
+            .clone()
+            .unwrap_or_else(|| self.definition.id.clone())
+    }
+
+    fn command_args(&self, options: &AgentStartOptions) -> anyhow::Result<Vec<String>> {
+        let mut args = match &self.definition.resume_args {
+            Some(resume_args) if options.resume => resume_args.clone(),
+            _ => self.definition.args.clone(),
+        };
+        if let (Some(prompt), Some(prompt_args)) = (&options.prompt, &self.definition.prompt_args) {
+            args.extend(prompt_args.iter().map(|arg| arg.replace("{prompt}", prompt)));
+        }
+        args.extend(self.enforce(&options.forbidden).args);
+        args.extend(self.launch_args(&options.launch)?);
+        Ok(args)
+    }
+}
+
+impl StatusTracker for CustomAgent {
+    fn status_from_output(&self, raw: &[u8], timestamp: Instant) -> Option<TaskStatus> {
+        let mut state = self.state.lock();
+        state.last_output = Some(timestamp);
+        state.screen.process(raw);
+        let screen_text = state.screen.full_text();
+        let matches = |pattern: &Option<Regex>| {
+            pattern
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(&screen_text))
+        };
+        let status = if matches(&self.approval_pattern) {
+            TaskStatus::AwaitingApproval
+        } else if matches(&self.idle_pattern) {
+            TaskStatus::Idle
+        } else {
+            TaskStatus::Working
+        };
+        let status_changed = state.last_status != Some(status);
+        if status_changed {
+            state.last_status = Some(status);
+        }
+        if status_changed { Some(status) } else { None }
+    }
+
+    fn status_if_idle(&self, now: Instant) -> Option<TaskStatus> {
+        // A configured idle pattern replaces the silence heuristic.
+        if self.idle_pattern.is_some() {
+            return None;
+        }
+        let mut state = self.state.lock();
+        let last = state.last_output?;
+        if now.duration_since(last) >= Duration::from_millis(1000)
+            && state.last_status == Some(TaskStatus::Working)
+        {
+            state.last_status = Some(TaskStatus::Idle);
+            return Some(TaskStatus::Idle);
+        }
+        None
+    }
+}
+
+fn compile_pattern(agent_id: &str, pattern: &Option<String>) -> anyhow::Result<Option<Regex>> {
+    pattern
+        .as_deref()
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| {
+            Regex::new(pattern)
+                .with_context(|| format!("invalid pattern '{}' for agent {}", pattern, agent_id))
+        })
+        .transpose()
+}
+
+impl Agent for CustomAgent {
+    fn start(
+        &mut self,
+        worktree_path: &Path,
+        options: &AgentStartOptions,
+        callbacks: AgentCallbacks,
+        rows: u16,
+        cols: u16,
+    ) -> anyhow::Result<PtyProcess> {
+        let args = self.command_args(options)?;
+        #[cfg(target_os = "windows")]
+        let mut command = {
+            let arg_refs: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+            build_wsl_command(worktree_path, &self.definition.command, &arg_refs)
+        };
+
+        #[cfg(not(target_os = "windows"))]
+        let mut command = {
+            let mut command = CommandBuilder::new(&self.definition.command);
+            command.args(&args);
+            command.cwd(worktree_path);
+            command
+        };
+        for (key, value) in &self.definition.env {
+            command.env(key, value);
+        }
+
+        let process = spawn_agent(command, &self.label(), rows, cols, callbacks, self.clone())?;
+        if let (Some(prompt), None) = (&options.prompt, &self.definition.prompt_args) {
+            type_prompt(&process.writer, prompt)
+                .with_context(|| format!("failed to send prompt to {}", self.label()))?;
+        }
+        Ok(process)
+    }
+
+    fn reset(&mut self, rows: usize, cols: usize) {
+        let mut state = self.state.lock();
+        state.screen = Screen::new(rows, cols);
+        state.last_output = None;
+        state.last_status = None;
+    }
+
+    fn resize(&mut self, rows: usize, cols: usize) {
+        self.state.lock().screen.resize(rows, cols);
+    }
+
+    fn classify_exit(&self, exit_code: i32) -> AgentFailure {
+        classify_exit(&self.state.lock().screen.lines(), exit_code, &FailurePhrases::NONE)
+    }
+
+    fn last_output(&self) -> Option<Instant> {
+        self.state.lock().last_output
+    }
+
+    fn pending_approval(&self) -> Option<String> {
+        let pattern = self.approval_pattern.as_ref()?;
+        let screen_text = self.state.lock().screen.full_text();
+        let captures = pattern.captures(&screen_text)?;
+        captures
+            .name("command")
+            .or_else(|| captures.get(1))
+            .map(|capture| capture.as_str().trim().to_string())
+    }
+
+    fn approval_keys(&self, decision: ApprovalDecision) -> Option<String> {
+        let keys = &self.definition.approval_keys;
+        match decision {
+            ApprovalDecision::Approve => keys.approve.clone(),
+            ApprovalDecision::ApproveAlways => keys.approve_always.clone(),
+            ApprovalDecision::Deny => keys.deny.clone(),
+        }
+    }
+
+    fn enforce(&self, rules: &[ForbiddenRule]) -> Enforcement {
+        let mut enforcement = Enforcement::default();
+        for rule in rules {
+            match (&rule.kind, &self.definition.deny_args) {
+                (ForbiddenKind::Command { words, .. }, Some(deny_args)) => {
+                    let command = words.join(" ");
+                    enforcement
+                        .args
+                        .extend(deny_args.iter().map(|arg| arg.replace("{command}", &command)));
+                }
+                _ => enforcement.unenforced.push(rule.label.clone()),
+            }
+        }
+        enforcement
+    }
+
+    /// Illuc does not know a custom agent's flags, so only extra args pass through.
+    fn launch_args(&self, launch: &AgentLaunchOptions) -> anyhow::Result<Vec<String>> {
+        reject_unsupported(
+            &self.label(),
+            &[
+                ("model", launch.model.is_some()),
+                ("effort", launch.effort.is_some()),
+                ("profile", launch.profile.is_some()),
+                ("sandbox", launch.sandbox.is_some()),
+            ],
+        )?;
+        Ok(launch.extra_args.clone())
+    }
+}
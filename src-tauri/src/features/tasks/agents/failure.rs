@@ -0,0 +1,126 @@
+use crate::features::tasks::{AgentFailure, AgentKind, FailureReason};
+
+/// Exit code shells, including the WSL one agents run through on Windows, use for a
+/// command they cannot find.
+const COMMAND_NOT_FOUND_EXIT: i32 = 127;
+
+/// How many of the last non-empty screen lines are read after an exit; anything further
+/// up is the agent's work, which may mention logins or rate limits of its own.
+const TAIL_LINES: usize = 5;
+
+/// What an agent prints on its way out for each failure, matched ignoring case.
+pub struct FailurePhrases {
+    pub not_authenticated: &'static [&'static str],
+    pub rate_limited: &'static [&'static str],
+    pub network_unreachable: &'static [&'static str],
+}
+
+impl FailurePhrases {
+    /// For agents whose failure messages are unknown: only a missing command is told
+    /// apart from a crash.
+    pub const NONE: FailurePhrases = FailurePhrases {
+        not_authenticated: &[],
+        rate_limited: &[],
+        network_unreachable: &[],
+    };
+}
+
+/// Classifies an error from starting the agent's command.
+pub fn classify_spawn_error(err: &anyhow::Error) -> AgentFailure {
+    let not_found = err.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|err| err.kind() == std::io::ErrorKind::NotFound)
+            || cause.to_string().contains("doesn't exist on the filesystem")
+    });
+    if not_found {
+        AgentFailure::BinaryNotFound
+    } else {
+        AgentFailure::Crashed
+    }
+}
+
+/// Classifies a non-zero exit by its code and the last lines the agent printed.
+pub fn classify_exit(lines: &[String], exit_code: i32, phrases: &FailurePhrases) -> AgentFailure {
+    let tail: Vec<String> = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .rev()
+        .take(TAIL_LINES)
+        .map(str::to_lowercase)
+        .collect();
+    let shows = |patterns: &[&str]| {
+        patterns.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            tail.iter().any(|line| line.contains(&pattern))
+        })
+    };
+    if exit_code == COMMAND_NOT_FOUND_EXIT || shows(&["command not found"]) {
+        AgentFailure::BinaryNotFound
+    } else if shows(phrases.not_authenticated) {
+        AgentFailure::NotAuthenticated
+    } else if shows(phrases.rate_limited) {
+        AgentFailure::RateLimited
+    } else if shows(phrases.network_unreachable) {
+        AgentFailure::NetworkUnreachable
+    } else {
+        AgentFailure::Crashed
+    }
+}
+
+/// Adds the remediation for `failure` in terms of the agent that hit it.
+pub fn failure_reason(failure: AgentFailure, agent_kind: &AgentKind) -> FailureReason {
+    let hint = match (failure, agent_kind) {
+        (AgentFailure::BinaryNotFound, AgentKind::Codex) => {
+            "Install the Codex CLI (`npm install -g @openai/codex`) and make sure `codex` is \
+             on PATH."
+                .to_string()
+        }
+        (AgentFailure::BinaryNotFound, AgentKind::Copilot) => {
+            "Install the Copilot CLI (`npm install -g @github/copilot`) and make sure \
+             `copilot` is on PATH."
+                .to_string()
+        }
+        (AgentFailure::BinaryNotFound, AgentKind::Aider) => {
+            "Install aider (`python -m pip install aider-install && aider-install`) and make \
+             sure `aider` is on PATH."
+                .to_string()
+        }
+        (AgentFailure::BinaryNotFound, AgentKind::Custom(id)) => format!(
+            "Check the `command` of agent '{}' in the illuc config and that it is on PATH.",
+            id
+        ),
+        (AgentFailure::NotAuthenticated, AgentKind::Codex) => {
+            "Run `codex login` in a terminal, then start the task again.".to_string()
+        }
+        (AgentFailure::NotAuthenticated, AgentKind::Copilot) => {
+            "Run `copilot` in a terminal and sign in with `/login`, then start the task again."
+                .to_string()
+        }
+        (AgentFailure::NotAuthenticated, AgentKind::Aider) => {
+            "Set the API key for aider's model, such as `OPENAI_API_KEY` or \
+             `ANTHROPIC_API_KEY`, then start the task again."
+                .to_string()
+        }
+        (AgentFailure::NotAuthenticated, AgentKind::Custom(id)) => {
+            format!("Log in to agent '{}' in a terminal, then start the task again.", id)
+        }
+        (AgentFailure::RateLimited, _) => {
+            "The provider's rate limit or quota was reached. Wait before starting the task \
+             again, or check the account's plan and billing."
+                .to_string()
+        }
+        (AgentFailure::NetworkUnreachable, _) => {
+            "The provider could not be reached. Check the network connection and any proxy \
+             settings, then start the task again."
+                .to_string()
+        }
+        (AgentFailure::Crashed, _) => {
+            "The agent exited unexpectedly. Its terminal output may say why; starting the \
+             task again resumes its session."
+                .to_string()
+        }
+    };
+    FailureReason { failure, hint }
+}
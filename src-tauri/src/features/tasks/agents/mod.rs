@@ -1,38 +1,161 @@
 use parking_lot::Mutex;
-use portable_pty::{Child, MasterPty};
+use portable_pty::CommandBuilder;
 use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
-use crate::features::tasks::TaskStatus;
+use std::time::Instant;
+use crate::features::tasks::forbidden::{Enforcement, ForbiddenRule};
+use crate::features::tasks::pty::{PtyHandlers, PtyProcess};
+use crate::features::tasks::{AgentFailure, AgentLaunchOptions, ApprovalDecision, TaskStatus};
 
+pub mod aider;
 pub mod codex;
 pub mod copilot;
+pub mod custom;
+pub mod failure;
+pub mod sessions;
 
-pub type ChildHandle = Box<dyn Child + Send + Sync>;
-
-pub struct AgentRuntime {
-    pub child: Arc<Mutex<ChildHandle>>,
-    pub writer: Arc<Mutex<Box<dyn Write + Send>>>,
-    pub master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
-}
+/// Terminal bracketed-paste markers: input between them is taken as pasted text rather
+/// than typed keys.
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
 
 #[derive(Clone)]
 pub struct AgentCallbacks {
     pub on_output: Arc<dyn Fn(String) + Send + Sync>,
     pub on_status: Arc<dyn Fn(TaskStatus) + Send + Sync>,
+    /// Called by the PTY supervisor every 250 ms while the agent runs.
+    pub on_tick: Arc<dyn Fn(Instant) + Send + Sync>,
     pub on_exit: Arc<dyn Fn(i32) + Send + Sync>,
 }
 
+/// How a particular run of an agent should begin.
+#[derive(Debug, Clone, Default)]
+pub struct AgentStartOptions {
+    /// First instruction for the agent.
+    pub prompt: Option<String>,
+    /// Continue the task's previous session instead of starting a new one.
+    pub resume: bool,
+    /// Specific session to resume, for agents that record sessions.
+    pub session_id: Option<String>,
+    /// Operations the agent must refuse, from the repo config.
+    pub forbidden: Vec<ForbiddenRule>,
+    pub launch: AgentLaunchOptions,
+}
+
+/// Reads an agent's status off its output, and off the silence between outputs.
+pub trait StatusTracker: Clone + Send + Sync + 'static {
+    fn status_from_output(&self, raw: &[u8], timestamp: Instant) -> Option<TaskStatus>;
+
+    fn status_if_idle(&self, now: Instant) -> Option<TaskStatus>;
+}
+
+/// Starts an agent on a PTY, reporting its output, status changes and exit through
+/// `callbacks`.
+pub fn spawn_agent(
+    command: CommandBuilder,
+    label: &str,
+    rows: u16,
+    cols: u16,
+    callbacks: AgentCallbacks,
+    tracker: impl StatusTracker,
+) -> anyhow::Result<PtyProcess> {
+    let output_tracker = tracker.clone();
+    let output_callbacks = callbacks.clone();
+    let tick_callbacks = callbacks.clone();
+    PtyProcess::spawn(
+        command,
+        label,
+        rows,
+        cols,
+        PtyHandlers {
+            on_output: Box::new(move |raw: &[u8]| {
+                if let Some(status) = output_tracker.status_from_output(raw, Instant::now()) {
+                    (output_callbacks.on_status)(status);
+                }
+                (output_callbacks.on_output)(String::from_utf8_lossy(raw).to_string());
+            }),
+            on_tick: Some(Box::new(move |now: Instant| {
+                if let Some(status) = tracker.status_if_idle(now) {
+                    (tick_callbacks.on_status)(status);
+                }
+                (tick_callbacks.on_tick)(now);
+            })),
+            on_exit: Box::new(move |exit_code: i32| (callbacks.on_exit)(exit_code)),
+        },
+    )
+}
+
+/// Types `prompt` into an agent that has no flag for an initial instruction. The PTY
+/// buffers it until the agent reads input. A prompt spanning several lines goes in as a
+/// bracketed paste, so its newlines stay in the prompt and only the final carriage
+/// return submits it.
+pub fn type_prompt(writer: &Mutex<Box<dyn Write + Send>>, prompt: &str) -> std::io::Result<()> {
+    let prompt = prompt.trim_end();
+    let mut writer = writer.lock();
+    if prompt.contains('\n') {
+        writer.write_all(BRACKETED_PASTE_START)?;
+        writer.write_all(prompt.as_bytes())?;
+        writer.write_all(BRACKETED_PASTE_END)?;
+    } else {
+        writer.write_all(prompt.as_bytes())?;
+    }
+    writer.write_all(b"\r")?;
+    writer.flush()
+}
+
+/// Fails naming the options in `unsupported` that are set, since `agent` has no flag
+/// for them.
+pub fn reject_unsupported(agent: &str, unsupported: &[(&str, bool)]) -> anyhow::Result<()> {
+    let names: Vec<&str> = unsupported
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "{} does not support the launch option(s): {}.",
+        agent,
+        names.join(", ")
+    ))
+}
+
+/// Removes the borders and selection markers that TUIs draw around dialogs.
+pub fn dialog_text(line: &str) -> &str {
+    line.trim_matches(|ch: char| ch.is_whitespace() || "│┃║▌›❯>".contains(ch))
+}
+
 pub trait Agent: Send + Sync {
     fn start(
         &mut self,
         worktree_path: &Path,
+        options: &AgentStartOptions,
         callbacks: AgentCallbacks,
         rows: u16,
         cols: u16,
-    ) -> anyhow::Result<AgentRuntime>;
+    ) -> anyhow::Result<PtyProcess>;
 
     fn reset(&mut self, rows: usize, cols: usize);
 
     fn resize(&mut self, rows: usize, cols: usize);
+
+    /// When the agent last printed anything in this run.
+    fn last_output(&self) -> Option<Instant>;
+
+    /// The command or tool call the agent is asking to run, read off its screen.
+    fn pending_approval(&self) -> Option<String>;
+
+    /// What to type to answer the approval prompt, if the agent supports `decision`.
+    fn approval_keys(&self, decision: ApprovalDecision) -> Option<String>;
+
+    /// Maps the forbidden rules onto the agent's own deny flags or sandbox settings.
+    fn enforce(&self, rules: &[ForbiddenRule]) -> Enforcement;
+
+    /// Flags for `launch`, or an error if the agent cannot honor one of its options.
+    fn launch_args(&self, launch: &AgentLaunchOptions) -> anyhow::Result<Vec<String>>;
+
+    /// Why the agent exited with `exit_code`, read off its last screen.
+    fn classify_exit(&self, exit_code: i32) -> AgentFailure;
 }
@@ -0,0 +1,83 @@
+use crate::features::tasks::models::AgentSession;
+#[cfg(target_os = "windows")]
+use crate::utils::windows::build_wsl_process_command;
+#[cfg(target_os = "windows")]
+use crate::utils::windows::to_wsl_path;
+use anyhow::Context;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The cwd an agent records for a worktree, as seen from where the agent runs.
+pub fn resolve_session_cwd(worktree_path: &Path) -> anyhow::Result<String> {
+    let canonical = fs::canonicalize(worktree_path)
+        .with_context(|| format!("failed to resolve cwd {}", worktree_path.display()))?;
+    #[cfg(target_os = "windows")]
+    if let Some(wsl_path) = to_wsl_path(&canonical) {
+        return Ok(wsl_path);
+    }
+    Ok(canonical.to_string_lossy().to_string())
+}
+
+/// Home directory of the user the agents run as, which is the WSL user on Windows.
+pub fn resolve_agent_home_dir(worktree_path: &Path) -> anyhow::Result<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        resolve_wsl_home_dir(worktree_path)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = worktree_path;
+        resolve_home_dir()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn resolve_home_dir() -> anyhow::Result<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .context("failed to resolve home directory")
+}
+
+#[cfg(target_os = "windows")]
+fn resolve_wsl_home_dir(worktree_path: &Path) -> anyhow::Result<PathBuf> {
+    let output = build_wsl_process_command(
+        worktree_path,
+        "bash",
+        &["-lc", "wslpath -w \"$HOME\""],
+    )
+    .output()
+    .context("failed to query WSL home directory")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("failed to query WSL home directory"));
+    }
+    let home = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if home.is_empty() {
+        return Err(anyhow::anyhow!("WSL home directory is empty"));
+    }
+    Ok(PathBuf::from(home))
+}
+
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let mut normalized = value.trim().to_string();
+    if normalized.ends_with('Z') {
+        normalized = format!("{}+00:00", normalized.trim_end_matches('Z'));
+    }
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(&normalized) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(&normalized, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+    None
+}
+
+/// Most recently active first; sessions without timestamps go last.
+pub fn sort_sessions(sessions: &mut [AgentSession]) {
+    sessions.sort_by(|a, b| {
+        let a_time = a.updated_at.or(a.started_at);
+        let b_time = b.updated_at.or(b.started_at);
+        b_time.cmp(&a_time)
+    });
+}
@@ -0,0 +1,357 @@
+use crate::error::{Result, TaskError};
+use crate::features::tasks::config::{load_repo_config, ApprovalRule, ApprovalRuleAction};
+use crate::features::tasks::forbidden::{load_forbidden_rules, ForbiddenRule};
+use crate::features::tasks::ApprovalDecision;
+use glob::Pattern;
+use regex::Regex;
+use std::path::{Component, Path, PathBuf};
+
+/// Shell syntax that runs or redirects more than the command it follows: lists and
+/// background jobs, pipes, redirections, and subshells or substitutions of any kind.
+const CHAINING_TOKENS: [&str; 8] = [";", "&", "|", ">", "<", "`", "(", ")"];
+
+/// Quoting and expansions that change what an argument names once the shell is done
+/// with it.
+const UNCHECKABLE_ARG_CHARS: [char; 5] = ['"', '\'', '\\', '$', '`'];
+
+/// A repo's approval rules, compiled once per prompt.
+pub struct ApprovalPolicy {
+    rules: Vec<CompiledRule>,
+    forbidden: Vec<ForbiddenRule>,
+}
+
+struct CompiledRule {
+    action: ApprovalRuleAction,
+    command: Option<Pattern>,
+    pattern: Option<Regex>,
+    paths: Vec<Pattern>,
+    label: String,
+}
+
+/// How a prompt was answered, and which rule answered it.
+pub struct PolicyVerdict {
+    pub decision: ApprovalDecision,
+    pub rule: String,
+}
+
+impl ApprovalPolicy {
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let rules = load_repo_config(repo_root)?
+            .approval_rules
+            .iter()
+            .map(CompiledRule::new)
+            .collect::<Result<Vec<_>>>()?;
+        let forbidden = load_forbidden_rules(repo_root)?;
+        Ok(Self { rules, forbidden })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.forbidden.is_empty()
+    }
+
+    /// Forbidden commands are always denied, and deny rules win over allow rules. Allow
+    /// rules never match a command that chains or redirects, so `cargo test*` cannot
+    /// approve `cargo test && rm -rf ~`.
+    pub fn evaluate(&self, command: &str, worktree_path: &Path) -> Option<PolicyVerdict> {
+        let command = command.trim();
+        if command.is_empty() {
+            return None;
+        }
+        if let Some(rule) = self
+            .forbidden
+            .iter()
+            .find(|rule| rule.matches(command, worktree_path))
+        {
+            return Some(PolicyVerdict {
+                decision: ApprovalDecision::Deny,
+                rule: format!("forbidden `{}`", rule.label),
+            });
+        }
+        let verdict = |action: ApprovalRuleAction, decision: ApprovalDecision| {
+            self.rules
+                .iter()
+                .filter(|rule| rule.action == action)
+                .find(|rule| rule.matches(command, worktree_path))
+                .map(|rule| PolicyVerdict {
+                    decision,
+                    rule: rule.label.clone(),
+                })
+        };
+        verdict(ApprovalRuleAction::Deny, ApprovalDecision::Deny).or_else(|| {
+            if chains_commands(command) {
+                return None;
+            }
+            verdict(ApprovalRuleAction::Allow, ApprovalDecision::Approve)
+        })
+    }
+}
+
+impl CompiledRule {
+    fn new(rule: &ApprovalRule) -> Result<Self> {
+        let invalid = |message: String| TaskError::Message(format!("Approval rule: {}", message));
+        if rule.command.is_none() && rule.pattern.is_none() {
+            return Err(invalid("needs a `command` glob or a `pattern`.".into()));
+        }
+        let command = rule
+            .command
+            .as_deref()
+            .map(|glob| {
+                Pattern::new(glob.trim())
+                    .map_err(|err| invalid(format!("invalid command glob '{}': {}", glob, err)))
+            })
+            .transpose()?;
+        let pattern = rule
+            .pattern
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|err| invalid(format!("invalid pattern '{}': {}", pattern, err)))
+            })
+            .transpose()?;
+        let paths = rule
+            .paths
+            .iter()
+            .map(|glob| {
+                Pattern::new(glob.trim())
+                    .map_err(|err| invalid(format!("invalid path glob '{}': {}", glob, err)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let action = match rule.action {
+            ApprovalRuleAction::Allow => "allow",
+            ApprovalRuleAction::Deny => "deny",
+        };
+        let matcher = match (&rule.command, &rule.pattern) {
+            (Some(command), Some(pattern)) => format!("`{}` /{}/", command, pattern),
+            (Some(command), None) => format!("`{}`", command),
+            (None, Some(pattern)) => format!("/{}/", pattern),
+            (None, None) => String::new(),
+        };
+        let label = if paths.is_empty() {
+            format!("{} {}", action, matcher)
+        } else {
+            format!("{} {} in {}", action, matcher, rule.paths.join(", "))
+        };
+        Ok(Self {
+            action: rule.action,
+            command,
+            pattern,
+            paths,
+            label,
+        })
+    }
+
+    fn matches(&self, command: &str, worktree_path: &Path) -> bool {
+        self.command
+            .as_ref()
+            .is_none_or(|glob| glob.matches(command))
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(command))
+            && self.paths_match(command, worktree_path)
+    }
+
+    /// Every argument has to fall inside the worktree and under one of the globs. A
+    /// command without arguments, or with one that cannot be checked, never matches.
+    fn paths_match(&self, command: &str, worktree_path: &Path) -> bool {
+        if self.paths.is_empty() {
+            return true;
+        }
+        command_paths(command).is_some_and(|paths| {
+            !paths.is_empty()
+                && paths.iter().all(|path| {
+                    worktree_relative(path, worktree_path).is_some_and(|relative| {
+                        self.paths.iter().any(|glob| glob.matches_path(&relative))
+                    })
+                })
+        })
+    }
+}
+
+fn chains_commands(command: &str) -> bool {
+    command.contains('\n') || CHAINING_TOKENS.iter().any(|token| command.contains(token))
+}
+
+/// Every argument after the command name taken as a path, globs included, along with
+/// the values of `--flag=value` options. `None` when an argument is quoted or expands
+/// variables, since what it names cannot be told without running the shell.
+pub fn command_paths(command: &str) -> Option<Vec<&str>> {
+    argument_paths(command.split_whitespace().skip(1))
+}
+
+/// The paths among a command's arguments, as `command_paths` reads them.
+pub fn argument_paths<'a>(args: impl IntoIterator<Item = &'a str>) -> Option<Vec<&'a str>> {
+    let mut paths = Vec::new();
+    let mut options_ended = false;
+    for arg in args {
+        // A glob such as `.*` can match `..` in older shells.
+        let dot_glob = arg
+            .split('/')
+            .any(|part| part.starts_with('.') && part.contains(['*', '?', '[']));
+        if arg.contains(UNCHECKABLE_ARG_CHARS) || dot_glob {
+            return None;
+        }
+        if options_ended || !arg.starts_with('-') {
+            paths.push(arg);
+        } else if arg == "--" {
+            options_ended = true;
+        } else if let Some((_, value)) = arg.split_once('=') {
+            paths.push(value);
+        }
+    }
+    Some(paths)
+}
+
+/// Resolves `path` against the worktree without touching disk; paths that leave the
+/// worktree give `None`.
+pub fn worktree_relative(path: &str, worktree_path: &Path) -> Option<PathBuf> {
+    if path.starts_with('~') {
+        return None;
+    }
+    let mut resolved = PathBuf::new();
+    for component in worktree_path.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+        .strip_prefix(worktree_path)
+        .ok()
+        .map(Path::to_path_buf)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        action: ApprovalRuleAction,
+        command: Option<&str>,
+        pattern: Option<&str>,
+        paths: &[&str],
+    ) -> ApprovalRule {
+        ApprovalRule {
+            action,
+            command: command.map(str::to_string),
+            pattern: pattern.map(str::to_string),
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+        }
+    }
+
+    fn policy(rules: &[ApprovalRule]) -> ApprovalPolicy {
+        ApprovalPolicy {
+            rules: rules.iter().map(|rule| CompiledRule::new(rule).unwrap()).collect(),
+            forbidden: Vec::new(),
+        }
+    }
+
+    fn decision(policy: &ApprovalPolicy, command: &str) -> Option<ApprovalDecision> {
+        policy
+            .evaluate(command, Path::new("/work/tree"))
+            .map(|verdict| verdict.decision)
+    }
+
+    #[test]
+    fn allow_rules_never_match_chained_commands() {
+        let policy = policy(&[rule(ApprovalRuleAction::Allow, Some("cargo test*"), None, &[])]);
+
+        assert_eq!(decision(&policy, "cargo test --workspace"), Some(ApprovalDecision::Approve));
+        for command in [
+            "cargo test; rm -rf ~",
+            "cargo test && rm -rf ~",
+            "cargo test || rm -rf ~",
+            "cargo test & rm -rf ~",
+            "cargo test | sh",
+            "cargo test\nrm -rf ~",
+            "cargo test > ~/.bashrc",
+            "cargo test `rm -rf ~`",
+            "cargo test $(rm -rf ~)",
+            "cargo test (rm -rf ~)",
+            "cargo test >(rm -rf ~)",
+            "cargo test <(rm -rf ~)",
+        ] {
+            assert_eq!(decision(&policy, command), None, "{command}");
+        }
+    }
+
+    #[test]
+    fn deny_rules_win_even_in_chains() {
+        let policy = policy(&[
+            rule(ApprovalRuleAction::Allow, Some("cargo test*"), None, &[]),
+            rule(ApprovalRuleAction::Deny, None, Some("git push"), &[]),
+        ]);
+
+        assert_eq!(
+            decision(&policy, "cargo test && git push"),
+            Some(ApprovalDecision::Deny)
+        );
+        assert_eq!(decision(&policy, "cargo test -- git push"), Some(ApprovalDecision::Deny));
+        assert_eq!(decision(&policy, "  "), None);
+    }
+
+    #[test]
+    fn path_rules_need_every_argument_inside_their_globs() {
+        let policy = policy(&[rule(
+            ApprovalRuleAction::Allow,
+            None,
+            Some("^rm -rf "),
+            &["target/**"],
+        )]);
+
+        for command in [
+            "rm -rf target/debug",
+            "rm -rf target/debug target/release",
+            "rm -rf target/*",
+            "rm -rf /work/tree/target/debug",
+            "rm -rf src/../target/debug",
+        ] {
+            assert_eq!(decision(&policy, command), Some(ApprovalDecision::Approve), "{command}");
+        }
+        for command in [
+            "rm -rf src",
+            "rm -rf target/debug src",
+            "rm -rf /tmp",
+            "rm -rf ../target",
+            "rm -rf target/../src",
+            "rm -rf ~/target",
+            "rm -rf \"target/debug\"",
+            "rm -rf 'target/debug'",
+            "rm -rf $HOME/target",
+            "rm -rf target/\\*",
+            "rm -rf target/.*",
+            "rm -rf ",
+        ] {
+            assert_eq!(decision(&policy, command), None, "{command}");
+        }
+    }
+
+    #[test]
+    fn command_paths_reads_arguments_and_option_values() {
+        assert_eq!(command_paths("rm -rf a b"), Some(vec!["a", "b"]));
+        assert_eq!(command_paths("rm -- -rf a"), Some(vec!["-rf", "a"]));
+        assert_eq!(command_paths("cp --target-directory=out a"), Some(vec!["out", "a"]));
+        assert_eq!(command_paths("ls"), Some(Vec::new()));
+        assert_eq!(command_paths("rm \"a b\""), None);
+        assert_eq!(command_paths("rm $TMPDIR"), None);
+        assert_eq!(command_paths("rm .?"), None);
+    }
+
+    #[test]
+    fn worktree_relative_resolves_without_leaving_the_worktree() {
+        let worktree = Path::new("/work/tree");
+
+        assert_eq!(worktree_relative("src/main.rs", worktree), Some(PathBuf::from("src/main.rs")));
+        assert_eq!(worktree_relative("./a/../b", worktree), Some(PathBuf::from("b")));
+        assert_eq!(worktree_relative("/work/tree/b", worktree), Some(PathBuf::from("b")));
+        assert_eq!(worktree_relative("..", worktree), None);
+        assert_eq!(worktree_relative("a/../../other", worktree), None);
+        assert_eq!(worktree_relative("/etc", worktree), None);
+        assert_eq!(worktree_relative("/work/treehouse", worktree), None);
+        assert_eq!(worktree_relative("~/src", worktree), None);
+    }
+}
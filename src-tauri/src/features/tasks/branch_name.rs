@@ -0,0 +1,152 @@
+use crate::error::{Result, TaskError};
+use crate::features::tasks::git::{check_branch_name, list_refs, ref_exists, run_git};
+use serde::Serialize;
+use std::path::Path;
+use thiserror::Error;
+
+const MAX_SLUG_LEN: usize = 48;
+
+/// Why a task branch name cannot be used, reported before anything touches disk.
+#[derive(Debug, Clone, Serialize, Error)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BranchNameError {
+    #[error("Branch name is required.")]
+    Missing,
+    #[error("Branch template '{template}' needs a {{{placeholder}}} value, but none was found.")]
+    MissingPlaceholder {
+        template: String,
+        placeholder: String,
+    },
+    #[error("'{name}' is not a valid branch name: {reason}")]
+    Invalid { name: String, reason: String },
+    #[error("Branch '{name}' already exists.")]
+    Exists { name: String },
+    #[error("Branch '{name}' conflicts with existing branch '{existing}'.")]
+    Conflict { name: String, existing: String },
+    #[error("Branch '{name}' is already checked out in worktree {worktree}.")]
+    CheckedOut { name: String, worktree: String },
+}
+
+/// Fills a template such as `agent/{user}/{slug}` or `{ticket}-{slug}` from the task title.
+pub fn render_branch_template(repo_root: &Path, template: &str, title: &str) -> Result<String> {
+    let ticket = extract_ticket(title);
+    let slug_source = match &ticket {
+        Some(ticket) => title.replacen(ticket.as_str(), " ", 1),
+        None => title.to_string(),
+    };
+    let mut name = template.trim().to_string();
+    for placeholder in ["user", "slug", "ticket"] {
+        let token = format!("{{{}}}", placeholder);
+        if !name.contains(&token) {
+            continue;
+        }
+        let value = match placeholder {
+            "user" => git_user(repo_root),
+            "slug" => Some(slugify(&slug_source, MAX_SLUG_LEN)),
+            _ => ticket.clone(),
+        }
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| BranchNameError::MissingPlaceholder {
+            template: template.to_string(),
+            placeholder: placeholder.to_string(),
+        })?;
+        name = name.replace(&token, &value);
+    }
+    Ok(name)
+}
+
+/// Checks that `name` is a legal branch name that can be created without
+/// clashing with an existing branch.
+pub fn validate_new_branch(repo_root: &Path, name: &str) -> Result<()> {
+    if let Some(reason) = check_branch_name(repo_root, name) {
+        return Err(BranchNameError::Invalid {
+            name: name.to_string(),
+            reason,
+        }
+        .into());
+    }
+    if ref_exists(repo_root, &format!("refs/heads/{}", name)) {
+        return Err(BranchNameError::Exists {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    // Refs are files, so `a/b` cannot coexist with a branch `a` or `a/b/c`.
+    let mut prefix = String::new();
+    for part in name.split('/') {
+        if !prefix.is_empty() && ref_exists(repo_root, &format!("refs/heads/{}", prefix)) {
+            return Err(conflict(name, &prefix));
+        }
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(part);
+    }
+    if let Some(existing) = list_refs(repo_root, &format!("refs/heads/{}/", name))?.first() {
+        return Err(conflict(name, existing));
+    }
+    Ok(())
+}
+
+fn conflict(name: &str, existing: &str) -> TaskError {
+    BranchNameError::Conflict {
+        name: name.to_string(),
+        existing: existing.to_string(),
+    }
+    .into()
+}
+
+/// First word of the title that looks like an issue key (`ABC-123`) or number (`#123`).
+fn extract_ticket(title: &str) -> Option<String> {
+    title
+        .split_whitespace()
+        .map(|word| word.trim_matches(|ch: char| !ch.is_ascii_alphanumeric() && ch != '-'))
+        .find(|word| is_issue_key(word) || (word.len() >= 3 && is_number(word)))
+        .map(|word| word.to_string())
+}
+
+fn is_issue_key(word: &str) -> bool {
+    match word.split_once('-') {
+        Some((project, number)) => {
+            project.starts_with(|ch: char| ch.is_ascii_alphabetic())
+                && project.chars().all(|ch| ch.is_ascii_alphanumeric())
+                && is_number(number)
+        }
+        None => false,
+    }
+}
+
+fn is_number(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|ch| ch.is_ascii_digit())
+}
+
+fn git_user(repo_root: &Path) -> Option<String> {
+    let name = run_git(repo_root, ["config", "user.name"])
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())?;
+    Some(slugify(&name, MAX_SLUG_LEN))
+}
+
+/// Lowercase ASCII words joined by `-`, cut at a word boundary where possible.
+fn slugify(value: &str, max_len: usize) -> String {
+    let mut slug = String::new();
+    for word in value
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let extra = if slug.is_empty() { word.len() } else { word.len() + 1 };
+        if slug.len() + extra > max_len {
+            if slug.is_empty() {
+                slug.push_str(&word[..max_len].to_ascii_lowercase());
+            }
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug
+}
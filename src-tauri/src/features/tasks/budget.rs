@@ -0,0 +1,59 @@
+use crate::features::tasks::{AgentBudgets, Budget, TaskStatus};
+use std::time::{Duration, Instant};
+
+/// Times one run of an agent against its budgets.
+pub struct BudgetClock {
+    budgets: AgentBudgets,
+    started: Instant,
+    status_since: Instant,
+    exceeded: bool,
+}
+
+impl BudgetClock {
+    pub fn new(budgets: AgentBudgets, now: Instant) -> Self {
+        Self {
+            budgets,
+            started: now,
+            status_since: now,
+            exceeded: false,
+        }
+    }
+
+    pub fn status_changed(&mut self, now: Instant) {
+        self.status_since = now;
+    }
+
+    /// The budget `status` has run past, reported only once per run. The working budget
+    /// counts from `last_output` when the agent printed something since it started working.
+    pub fn check(
+        &mut self,
+        status: TaskStatus,
+        last_output: Option<Instant>,
+        now: Instant,
+    ) -> Option<(Budget, u64)> {
+        if self.exceeded {
+            return None;
+        }
+        let exceeded = [
+            (Budget::Runtime, self.started),
+            (
+                Budget::Working,
+                last_output.map_or(self.status_since, |last| self.status_since.max(last)),
+            ),
+            (Budget::AwaitingApproval, self.status_since),
+        ]
+        .into_iter()
+        .filter(|(budget, _)| match budget {
+            Budget::Runtime => true,
+            Budget::Working => status == TaskStatus::Working,
+            Budget::AwaitingApproval => status == TaskStatus::AwaitingApproval,
+        })
+        .find_map(|(budget, since)| {
+            let limit_secs = self.budgets.limit_secs(budget)?;
+            (now.duration_since(since) >= Duration::from_secs(limit_secs))
+                .then_some((budget, limit_secs))
+        });
+        self.exceeded = exceeded.is_some();
+        exceeded
+    }
+}
@@ -0,0 +1,170 @@
+use crate::error::{Result, TaskError};
+use crate::features::tasks::{AgentBudgets, AgentLaunchOptions, RestartPolicy};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "config.json";
+
+/// Per-repository settings read from `<repo>/.illuc/config.json`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RepoConfig {
+    pub setup: SetupConfig,
+    /// Directory that holds this repo's task worktrees; overrides the global setting.
+    pub worktree_root: Option<String>,
+    /// Generates branch names for tasks created without one, e.g. `agent/{user}/{slug}`.
+    /// `{ticket}` is an issue key such as `ABC-123` taken from the task title.
+    pub branch_template: Option<String>,
+    /// Agents available to this repo's tasks, on top of the user-wide ones.
+    pub agents: Vec<AgentDefinition>,
+    /// Answer approval prompts whose command matches, without waiting for the user.
+    pub approval_rules: Vec<ApprovalRule>,
+    /// Operations no agent may run: `push`, `forceReset`, `deleteOutsideWorktree`,
+    /// `publish`, or command prefixes such as `terraform apply`. Defaults to `push`.
+    pub forbidden: Option<Vec<String>>,
+    /// Default launch options per agent id; `task_start` options override them.
+    pub launch_options: HashMap<String, AgentLaunchOptions>,
+    /// How long a stopped agent gets to exit after Ctrl-C before its process group is
+    /// killed.
+    pub stop_grace_period_ms: Option<u64>,
+    /// Limits after which a task's agent is stopped; `task_start` budgets override them.
+    pub budgets: AgentBudgets,
+    /// Whether agents that exit with an error are started again; `task_start` can
+    /// override it.
+    pub restart_policy: RestartPolicy,
+}
+
+/// User-wide settings read from `<config dir>/illuc/config.json`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GlobalConfig {
+    /// Directory under which each repo gets its own folder of task worktrees.
+    pub worktree_root: Option<String>,
+    pub agents: Vec<AgentDefinition>,
+}
+
+/// A CLI agent run in the task's PTY like the built-in ones.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentDefinition {
+    /// Passed as `agent` to `task_start`.
+    pub id: String,
+    pub name: Option<String>,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Used instead of `args` once the task has run before.
+    pub resume_args: Option<Vec<String>>,
+    /// Appended when the run has a prompt, with `{prompt}` replaced by it. Without
+    /// them the prompt is typed into the agent's terminal.
+    pub prompt_args: Option<Vec<String>>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Regex matched against the screen; a match means the agent waits for approval.
+    /// A `command` group, or else the first group, captures what is to be approved.
+    pub approval_pattern: Option<String>,
+    /// Keystrokes that answer the approval prompt.
+    #[serde(default)]
+    pub approval_keys: ApprovalKeys,
+    /// Appended once per forbidden command, with `{command}` replaced by it, e.g.
+    /// `["--deny", "{command}"]`. Without them forbidden commands are unenforced.
+    pub deny_args: Option<Vec<String>>,
+    /// Regex matched against the screen; a match means the agent is idle. Without
+    /// one, a second without output counts as idle.
+    pub idle_pattern: Option<String>,
+}
+
+/// Text written to the agent's terminal for each answer, e.g. `"y\r"`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApprovalKeys {
+    pub approve: Option<String>,
+    pub approve_always: Option<String>,
+    pub deny: Option<String>,
+}
+
+/// Matches the command an agent asks to run. A rule needs `command`, `pattern` or both.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRule {
+    pub action: ApprovalRuleAction,
+    /// Glob matched against the whole command, e.g. `cargo test*`.
+    pub command: Option<String>,
+    /// Regex searched for in the command.
+    pub pattern: Option<String>,
+    /// Globs, relative to the worktree, that every path in the command must match.
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalRuleAction {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SetupConfig {
+    /// Shell commands run in order inside every new worktree. Ignored when
+    /// `.illuc/setup.sh` exists.
+    pub commands: Vec<String>,
+    /// Globs, relative to the base repo root, of ignored files copied into new worktrees.
+    pub copy: Vec<String>,
+    /// Like `copy`, but symlinked so edits stay shared with the base repo.
+    pub symlink: Vec<String>,
+}
+
+pub fn illuc_dir(repo_root: &Path) -> PathBuf {
+    repo_root.join(".illuc")
+}
+
+pub fn load_repo_config(repo_root: &Path) -> Result<RepoConfig> {
+    read_config(&illuc_dir(repo_root).join(CONFIG_FILE))
+}
+
+pub fn load_global_config() -> Result<GlobalConfig> {
+    match dirs::config_dir() {
+        Some(dir) => read_config(&dir.join("illuc").join(CONFIG_FILE)),
+        None => Ok(GlobalConfig::default()),
+    }
+}
+
+/// Finds the agent `id` in the repo config, falling back to the user-wide config.
+pub fn find_agent_definition(repo_root: &Path, id: &str) -> Result<AgentDefinition> {
+    let repo_agents = load_repo_config(repo_root)?.agents;
+    let global_agents = load_global_config()?.agents;
+    repo_agents
+        .into_iter()
+        .chain(global_agents)
+        .find(|agent| agent.id == id)
+        .ok_or_else(|| TaskError::Message(format!("Agent '{}' is not configured.", id)))
+}
+
+fn read_config<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let data = std::fs::read_to_string(path)?;
+    serde_json::from_str(&data)
+        .map_err(|err| TaskError::Message(format!("invalid {}: {}", path.display(), err)))
+}
+
+/// Expands a leading `~` and resolves relative paths against `base`.
+pub fn resolve_config_path(value: &str, base: &Path) -> PathBuf {
+    let value = value.trim();
+    let expanded = match value.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => dirs::home_dir()
+            .map(|home| home.join(rest.trim_start_matches(['/', '\\'])))
+            .unwrap_or_else(|| PathBuf::from(value)),
+        _ => PathBuf::from(value),
+    };
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        base.join(expanded)
+    }
+}
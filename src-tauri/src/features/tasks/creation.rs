@@ -0,0 +1,298 @@
+use crate::error::{Result, TaskError};
+use crate::features::tasks::config::load_repo_config;
+use crate::features::tasks::events::{
+    emit_status, emit_terminal_output, emit_worktree_progress,
+};
+use crate::features::tasks::git::{
+    lfs_installed, run_git, run_git_streaming, sparse_checkout_args, submodule_paths, uses_lfs,
+    GitCancelHandle,
+};
+use crate::features::tasks::local_files::transfer_local_files;
+use crate::features::tasks::setup::{run_setup, setup_plan};
+use crate::features::tasks::worktree::BranchCheckout;
+use crate::features::tasks::{
+    resolve_fork_point, StartTaskRequest, TaskManager, TaskStatus, TerminalKind,
+};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+/// Everything the background creation thread needs to populate a task's worktree.
+pub struct WorktreeCreation {
+    pub task_id: Uuid,
+    pub repo_root: PathBuf,
+    pub worktree_path: PathBuf,
+    pub checkout: BranchCheckout,
+    pub base_branch: String,
+    pub sparse_paths: Vec<String>,
+}
+
+impl TaskManager {
+    pub(super) fn run_worktree_creation(
+        &self,
+        creation: WorktreeCreation,
+        cancel: GitCancelHandle,
+        app: &AppHandle,
+    ) {
+        let task_id = creation.task_id;
+        let fork_point = match self.populate_worktree(&creation, &cancel, app) {
+            Ok(fork_point) => fork_point,
+            Err(err) => {
+                remove_partial_worktree(&creation);
+                self.abort_worktree_creation(task_id, &cancel, err, app);
+                return;
+            }
+        };
+        info!("worktree created task_id={}", task_id);
+        if let Some(fork_point) = fork_point {
+            if let Some(record) = self.inner.tasks.write().get_mut(&task_id) {
+                record.summary.base_commit = fork_point;
+            }
+        }
+
+        let config = match load_repo_config(&creation.repo_root) {
+            Ok(config) => config,
+            Err(err) => {
+                self.abort_worktree_creation(task_id, &cancel, err, app);
+                return;
+            }
+        };
+        if let Err(err) = transfer_local_files(
+            &creation.repo_root,
+            &creation.worktree_path,
+            &config.setup,
+            |message| emit_worktree_progress(app, task_id, message),
+        ) {
+            self.abort_worktree_creation(task_id, &cancel, err, app);
+            return;
+        }
+        let plan = setup_plan(&creation.repo_root, &config);
+        let Some(plan) = plan else {
+            self.finish_worktree_creation(task_id, TaskStatus::Stopped, None, app);
+            return;
+        };
+
+        {
+            let mut tasks = self.inner.tasks.write();
+            if let Some(record) = tasks.get_mut(&task_id) {
+                record.summary.status = TaskStatus::SettingUp;
+            }
+            self.publish_task(&tasks, task_id, app);
+        }
+        let output_app = app.clone();
+        let result = run_setup(
+            &plan,
+            task_id,
+            &creation.repo_root,
+            &creation.worktree_path,
+            &cancel,
+            move |chunk| emit_terminal_output(&output_app, task_id, chunk, TerminalKind::Setup),
+        );
+        match result {
+            Ok(0) => {
+                info!("worktree setup finished task_id={}", task_id);
+                self.finish_worktree_creation(task_id, TaskStatus::Stopped, None, app);
+            }
+            Ok(exit_code) => {
+                warn!("worktree setup failed task_id={} exit_code={}", task_id, exit_code);
+                emit_worktree_progress(
+                    app,
+                    task_id,
+                    format!("error: setup exited with code {}", exit_code),
+                );
+                self.finish_worktree_creation(task_id, TaskStatus::Failed, Some(exit_code), app);
+            }
+            Err(err) => {
+                if cancel.is_cancelled() {
+                    remove_partial_worktree(&creation);
+                }
+                self.abort_worktree_creation(task_id, &cancel, err, app);
+            }
+        }
+    }
+
+    fn finish_worktree_creation(
+        &self,
+        task_id: Uuid,
+        status: TaskStatus,
+        exit_code: Option<i32>,
+        app: &AppHandle,
+    ) {
+        let has_prompt = {
+            let mut tasks = self.inner.tasks.write();
+            let mut has_prompt = false;
+            if let Some(record) = tasks.get_mut(&task_id) {
+                record.creation = None;
+                record.summary.status = status;
+                record.summary.exit_code = exit_code;
+                if status == TaskStatus::Failed {
+                    record.summary.ended_at = Some(chrono::Utc::now());
+                }
+                has_prompt = record.summary.prompt.is_some();
+            }
+            self.publish_task(&tasks, task_id, app);
+            has_prompt
+        };
+        // A task created with a prompt goes straight to work.
+        if status == TaskStatus::Stopped && has_prompt {
+            let req = StartTaskRequest {
+                task_id,
+                cols: None,
+                rows: None,
+                agent: None,
+                prompt: None,
+                session_id: None,
+                new_session: false,
+                launch_options: None,
+                budgets: None,
+                restart_policy: None,
+            };
+            if let Err(err) = self.start_task(req, app) {
+                warn!("failed to start agent task_id={} err={}", task_id, err);
+                emit_worktree_progress(app, task_id, format!("error: {}", err));
+            }
+        }
+    }
+
+    /// A cancelled task disappears entirely; a failed one stays visible with its error.
+    fn abort_worktree_creation(
+        &self,
+        task_id: Uuid,
+        cancel: &GitCancelHandle,
+        err: TaskError,
+        app: &AppHandle,
+    ) {
+        if cancel.is_cancelled() {
+            info!("worktree creation cancelled task_id={}", task_id);
+            let mut tasks = self.inner.tasks.write();
+            if let Some(mut record) = tasks.remove(&task_id) {
+                record.summary.status = TaskStatus::Discarded;
+                emit_status(app, &record.summary);
+                self.persist_tasks(&tasks, &record.summary.base_repo_path);
+            }
+            return;
+        }
+        warn!("worktree creation failed task_id={} err={}", task_id, err);
+        emit_worktree_progress(app, task_id, format!("error: {}", err));
+        self.finish_worktree_creation(task_id, TaskStatus::Failed, None, app);
+    }
+
+    /// Adds the worktree and checks its files out, returning the fork point when
+    /// it can only be known once the branch is in place.
+    fn populate_worktree(
+        &self,
+        creation: &WorktreeCreation,
+        cancel: &GitCancelHandle,
+        app: &AppHandle,
+    ) -> Result<Option<String>> {
+        let task_id = creation.task_id;
+        let progress = |line: &str| emit_worktree_progress(app, task_id, line.to_string());
+        let worktree_path = creation.worktree_path.to_string_lossy().to_string();
+        run_git_streaming(
+            &creation.repo_root,
+            creation.checkout.worktree_add_args(&worktree_path),
+            cancel,
+            progress,
+        )?;
+        // Restrict the checkout before any files are written.
+        if !creation.sparse_paths.is_empty() {
+            run_git_streaming(
+                &creation.worktree_path,
+                sparse_checkout_args(&creation.sparse_paths),
+                cancel,
+                progress,
+            )?;
+        }
+        run_git_streaming(
+            &creation.worktree_path,
+            ["checkout", "--progress"],
+            cancel,
+            progress,
+        )?;
+        init_submodules(creation, cancel, progress)?;
+        if uses_lfs(&creation.worktree_path) {
+            if lfs_installed(&creation.worktree_path) {
+                // LFS keeps its objects in the common git dir, so worktrees share them.
+                run_git_streaming(&creation.worktree_path, ["lfs", "pull"], cancel, progress)?;
+            } else {
+                progress("warning: repo uses Git LFS but git-lfs is not installed");
+            }
+        }
+        Ok(match creation.checkout {
+            BranchCheckout::New { .. } => None,
+            _ => resolve_fork_point(&creation.worktree_path, &creation.base_branch),
+        })
+    }
+}
+
+/// Initializes the submodules present in the checkout, borrowing objects from
+/// the base repo's copy of each submodule when it has one.
+fn init_submodules(
+    creation: &WorktreeCreation,
+    cancel: &GitCancelHandle,
+    progress: impl FnMut(&str) + Copy,
+) -> Result<()> {
+    // Submodules outside a sparse checkout have no directory and are left alone.
+    let paths: Vec<String> = submodule_paths(&creation.worktree_path)?
+        .into_iter()
+        .filter(|path| creation.worktree_path.join(path).is_dir())
+        .collect();
+    for path in &paths {
+        let reference = creation.repo_root.join(path);
+        if is_populated_checkout(&reference) {
+            let reference = reference.to_string_lossy().to_string();
+            run_git_streaming(
+                &creation.worktree_path,
+                [
+                    "submodule",
+                    "update",
+                    "--init",
+                    "--progress",
+                    "--reference",
+                    reference.as_str(),
+                    "--",
+                    path.as_str(),
+                ],
+                cancel,
+                progress,
+            )?;
+        }
+    }
+    if !paths.is_empty() {
+        // Fills in whatever the reference pass skipped, plus nested submodules.
+        let mut args = vec![
+            "submodule".to_string(),
+            "update".to_string(),
+            "--init".to_string(),
+            "--recursive".to_string(),
+            "--progress".to_string(),
+            "--".to_string(),
+        ];
+        args.extend(paths);
+        run_git_streaming(&creation.worktree_path, args, cancel, progress)?;
+    }
+    Ok(())
+}
+
+fn is_populated_checkout(path: &Path) -> bool {
+    path.join(".git").exists()
+}
+
+fn remove_partial_worktree(creation: &WorktreeCreation) {
+    let worktree_path = creation.worktree_path.to_string_lossy().to_string();
+    let _ = run_git(
+        &creation.repo_root,
+        ["worktree", "remove", "--force", worktree_path.as_str()],
+    );
+    if creation.worktree_path.exists() {
+        let _ = std::fs::remove_dir_all(&creation.worktree_path);
+    }
+    let _ = run_git(&creation.repo_root, ["worktree", "prune"]);
+    if creation.checkout.owns_branch() {
+        let _ = run_git(
+            &creation.repo_root,
+            ["branch", "-D", creation.checkout.local_name()],
+        );
+    }
+}
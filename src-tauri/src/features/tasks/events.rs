@@ -1,4 +1,4 @@
-use crate::features::tasks::{TaskSummary, TerminalKind};
+use crate::features::tasks::{AgentKind, Budget, TaskSummary, TerminalKind};
 use log::debug;
 use serde::Serialize;
 use tauri::{AppHandle, Emitter};
@@ -35,6 +35,43 @@ pub fn emit_diff_changed(app: &AppHandle, task_id: Uuid) {
     let _ = app.emit("task_diff_changed", payload);
 }
 
+pub fn emit_transcript_changed(app: &AppHandle, task_id: Uuid) {
+    debug!("emit task_transcript_changed task_id={}", task_id);
+    let payload = TranscriptChangedPayload { task_id };
+    let _ = app.emit("task_transcript_changed", payload);
+}
+
+pub fn emit_approval_requested(
+    app: &AppHandle,
+    task_id: Uuid,
+    agent: AgentKind,
+    command: Option<String>,
+) {
+    debug!("emit task_approval_requested task_id={} command={:?}", task_id, command);
+    let payload = ApprovalRequestedPayload {
+        task_id,
+        agent,
+        command,
+    };
+    let _ = app.emit("task_approval_requested", payload);
+}
+
+pub fn emit_worktree_progress(app: &AppHandle, task_id: Uuid, message: String) {
+    debug!("emit task_worktree_progress task_id={} message={}", task_id, message);
+    let payload = WorktreeProgressPayload { task_id, message };
+    let _ = app.emit("task_worktree_progress", payload);
+}
+
+pub fn emit_budget_exceeded(app: &AppHandle, task_id: Uuid, budget: Budget, limit_secs: u64) {
+    debug!("emit task_budget_exceeded task_id={} budget={:?}", task_id, budget);
+    let payload = BudgetExceededPayload {
+        task_id,
+        budget,
+        limit_secs,
+    };
+    let _ = app.emit("task_budget_exceeded", payload);
+}
+
 #[derive(Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct TerminalOutputPayload {
@@ -56,3 +93,32 @@ struct TerminalExitPayload {
 struct DiffChangedPayload {
     task_id: Uuid,
 }
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptChangedPayload {
+    task_id: Uuid,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ApprovalRequestedPayload {
+    task_id: Uuid,
+    agent: AgentKind,
+    command: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct WorktreeProgressPayload {
+    task_id: Uuid,
+    message: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct BudgetExceededPayload {
+    task_id: Uuid,
+    budget: Budget,
+    limit_secs: u64,
+}
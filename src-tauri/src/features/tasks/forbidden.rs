@@ -0,0 +1,296 @@
+use crate::error::Result;
+use crate::features::tasks::approval_policy::{argument_paths, worktree_relative};
+use crate::features::tasks::config::load_repo_config;
+use std::path::Path;
+
+/// Used when the repo config has no `forbidden` list, matching what agents were always
+/// started with.
+const DEFAULT_FORBIDDEN: [&str; 1] = ["push"];
+
+const PUBLISH_COMMANDS: [&str; 7] = [
+    "npm publish",
+    "yarn publish",
+    "pnpm publish",
+    "cargo publish",
+    "poetry publish",
+    "twine upload",
+    "gem push",
+];
+
+/// Commands that run the command after them, with their options that take a value.
+const WRAPPERS: [(&str, &[&str]); 11] = [
+    ("sudo", &["-u", "-g", "-U", "-C", "-D", "-h", "-p", "-r", "-t", "-T"]),
+    ("env", &["-u", "-C", "-S"]),
+    ("command", &[]),
+    ("nohup", &[]),
+    ("exec", &["-a"]),
+    ("time", &["-f", "-o"]),
+    ("nice", &["-n"]),
+    ("xargs", &["-a", "-d", "-E", "-I", "-L", "-n", "-P", "-s"]),
+    ("sh", &[]),
+    ("bash", &[]),
+    ("zsh", &[]),
+];
+
+/// Git options that come before the subcommand and take the next word as their value.
+const GIT_VALUE_OPTIONS: [&str; 6] =
+    ["-C", "-c", "--git-dir", "--work-tree", "--namespace", "--config-env"];
+
+/// An operation no agent may run, from `forbidden` in the repo config.
+#[derive(Debug, Clone)]
+pub struct ForbiddenRule {
+    /// How the rule is reported, e.g. `git push`.
+    pub label: String,
+    pub kind: ForbiddenKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ForbiddenKind {
+    /// Any command starting with these words. `network` commands cannot succeed
+    /// without network access.
+    Command { words: Vec<String>, network: bool },
+    /// `rm` with a path that leaves the worktree.
+    DeleteOutsideWorktree,
+}
+
+/// How an agent applies the forbidden rules: extra launch arguments, and the rules it
+/// has no way to enforce.
+#[derive(Debug, Default)]
+pub struct Enforcement {
+    pub args: Vec<String>,
+    pub unenforced: Vec<String>,
+}
+
+/// Entries are the operations `push`, `forceReset`, `deleteOutsideWorktree` and
+/// `publish`, or command prefixes such as `terraform apply`.
+pub fn load_forbidden_rules(repo_root: &Path) -> Result<Vec<ForbiddenRule>> {
+    let forbidden = load_repo_config(repo_root)?
+        .forbidden
+        .unwrap_or_else(|| DEFAULT_FORBIDDEN.iter().map(|entry| entry.to_string()).collect());
+    Ok(forbidden
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .flat_map(expand_entry)
+        .collect())
+}
+
+fn expand_entry(entry: &str) -> Vec<ForbiddenRule> {
+    match entry {
+        "push" => vec![ForbiddenRule::command("git push", true)],
+        "forceReset" => vec![ForbiddenRule::command("git reset --hard", false)],
+        "publish" => PUBLISH_COMMANDS
+            .iter()
+            .map(|command| ForbiddenRule::command(command, true))
+            .collect(),
+        "deleteOutsideWorktree" => vec![ForbiddenRule {
+            label: "rm outside the worktree".to_string(),
+            kind: ForbiddenKind::DeleteOutsideWorktree,
+        }],
+        command => vec![ForbiddenRule::command(command, false)],
+    }
+}
+
+impl ForbiddenRule {
+    fn command(command: &str, network: bool) -> Self {
+        Self {
+            label: command.to_string(),
+            kind: ForbiddenKind::Command {
+                words: command.split_whitespace().map(|word| word.to_string()).collect(),
+                network,
+            },
+        }
+    }
+
+    /// Checks each command of a chain such as `cd repo && git push` on its own, past
+    /// wrappers like `sudo` and git's global options. Options in the rule, such as
+    /// `--hard`, may appear anywhere after the subcommand.
+    pub fn matches(&self, command: &str, worktree_path: &Path) -> bool {
+        command
+            .split(['\n', ';', '|', '&', '(', ')', '`'])
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .any(|segment| match &self.kind {
+                ForbiddenKind::Command { words, .. } => {
+                    let command = command_words(segment);
+                    let (names, options): (Vec<&str>, Vec<&str>) = words
+                        .iter()
+                        .map(String::as_str)
+                        .partition(|word| !word.starts_with('-'));
+                    command.len() >= names.len()
+                        && command[..names.len()] == names[..]
+                        && options
+                            .iter()
+                            .all(|option| command[names.len()..].contains(option))
+                }
+                ForbiddenKind::DeleteOutsideWorktree => {
+                    // Arguments that cannot be checked count as leaving the worktree, and
+                    // so does an `rm` without paths, which `xargs` may be feeding.
+                    let command = command_words(segment);
+                    command.first() == Some(&"rm")
+                        && argument_paths(command[1..].iter().copied()).is_none_or(|paths| {
+                            paths.is_empty()
+                                || paths
+                                    .iter()
+                                    .any(|path| worktree_relative(path, worktree_path).is_none())
+                        })
+                }
+            })
+    }
+}
+
+/// The words of `segment` from the command that actually runs: leading variable
+/// assignments and wrappers are dropped, paths reduced to the program's name, and git's
+/// global options skipped so the subcommand follows `git`.
+fn command_words(segment: &str) -> Vec<&str> {
+    let mut words = segment
+        .split_whitespace()
+        .map(|word| word.trim_matches(|ch| ch == '"' || ch == '\''))
+        .filter(|word| !word.is_empty())
+        .peekable();
+    let mut command = Vec::new();
+    while let Some(word) = words.next() {
+        if is_assignment(word) {
+            continue;
+        }
+        let name = word.rsplit('/').next().unwrap_or(word);
+        if let Some((_, value_options)) = WRAPPERS.iter().find(|(wrapper, _)| *wrapper == name) {
+            while let Some(option) = words.next_if(|word| word.starts_with('-')) {
+                if value_options.contains(&option) {
+                    words.next();
+                }
+            }
+            continue;
+        }
+        command.push(name);
+        break;
+    }
+    if command.first() == Some(&"git") {
+        while let Some(option) = words.next_if(|word| word.starts_with('-')) {
+            if GIT_VALUE_OPTIONS.contains(&option) {
+                words.next();
+            }
+        }
+    }
+    command.extend(words);
+    command
+}
+
+/// `NAME=value` as a shell reads it before a command.
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && !name.starts_with(|ch: char| ch.is_ascii_digit())
+            && name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(entry: &str, command: &str) -> bool {
+        expand_entry(entry)
+            .iter()
+            .any(|rule| rule.matches(command, Path::new("/work/tree")))
+    }
+
+    #[test]
+    fn commands_match_in_any_part_of_a_chain() {
+        for command in [
+            "git push",
+            "git push origin main",
+            "cd repo && git push",
+            "echo hi; git push",
+            "true & git push",
+            "git status || git push",
+            "git log | git push",
+            "echo hi\ngit push",
+            "(git push)",
+            "echo `git push`",
+            "echo $(git push)",
+        ] {
+            assert!(matches("push", command), "{command}");
+        }
+        for command in ["git status", "git pushd", "echo git push", "git log --grep push"] {
+            assert!(!matches("push", command), "{command}");
+        }
+    }
+
+    #[test]
+    fn commands_match_behind_wrappers_assignments_and_git_options() {
+        for command in [
+            "sudo git push",
+            "sudo -u deploy git push",
+            "env GIT_TRACE=1 git push",
+            "GIT_SSH_COMMAND=ssh git push",
+            "nohup git push",
+            "command git push",
+            "xargs -n 1 git push",
+            "sh -c 'git push'",
+            "bash -c \"git push --force\"",
+            "/usr/bin/git push",
+            "git -C x push",
+            "git -c user.name=me push",
+            "git --git-dir=.git push",
+            "git --no-pager -C x push",
+        ] {
+            assert!(matches("push", command), "{command}");
+        }
+    }
+
+    #[test]
+    fn rule_options_match_anywhere_after_the_subcommand() {
+        for command in [
+            "git reset --hard",
+            "git reset --hard HEAD~1",
+            "git reset HEAD~1 --hard",
+            "git -C x reset -q --hard",
+        ] {
+            assert!(matches("forceReset", command), "{command}");
+        }
+        for command in ["git reset --soft HEAD~1", "git reset HEAD~1", "git --hard reset"] {
+            assert!(!matches("forceReset", command), "{command}");
+        }
+        assert!(matches("terraform apply", "terraform apply -auto-approve"));
+        assert!(!matches("terraform apply", "terraform plan"));
+        assert!(matches("publish", "cd pkg && npm publish --access public"));
+    }
+
+    #[test]
+    fn deletes_match_when_a_path_leaves_the_worktree() {
+        for command in [
+            "rm -rf /",
+            "rm -rf ~",
+            "rm -rf ../other",
+            "rm -rf target/../../other",
+            "rm src/a.rs /etc/hosts",
+            "sudo rm -rf /",
+            "/bin/rm -rf ~",
+            "X=1 rm -rf ~",
+            "env rm -rf ~",
+            "sh -c 'rm -rf ~'",
+            "cd src && rm -rf /tmp/x",
+            "rm -rf $HOME",
+            "rm -rf \"$HOME\"",
+            "rm -rf ~user",
+            "rm -rf .*",
+            "find / -name x | xargs rm -rf",
+        ] {
+            assert!(matches("deleteOutsideWorktree", command), "{command}");
+        }
+        for command in [
+            "rm -rf target",
+            "rm src/a.rs src/b.rs",
+            "rm -rf /work/tree/target",
+            "sudo rm -rf target",
+            "rm -rf 'target'",
+            "rm -- -file",
+            "echo rm -rf /",
+            "ls /",
+        ] {
+            assert!(!matches("deleteOutsideWorktree", command), "{command}");
+        }
+    }
+}
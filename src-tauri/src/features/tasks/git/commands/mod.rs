@@ -4,3 +4,4 @@ pub mod task_git_diff_watch_start;
 pub mod task_git_diff_watch_stop;
 pub mod task_git_list_branches;
 pub mod task_git_push;
+pub mod task_git_sparse_set;
@@ -0,0 +1,25 @@
+use crate::commands::CommandResult;
+use crate::features::tasks::{TaskManager, TaskSummary};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub task_id: Uuid,
+    /// Cone directories to keep checked out; empty restores a full checkout.
+    pub paths: Vec<String>,
+}
+
+pub type Response = TaskSummary;
+
+#[tauri::command]
+pub async fn task_git_sparse_set(
+    manager: tauri::State<'_, TaskManager>,
+    app_handle: tauri::AppHandle,
+    req: Request,
+) -> CommandResult<Response> {
+    manager
+        .set_sparse_paths(req, &app_handle)
+        .map_err(|err| err.to_string())
+}
@@ -1,8 +1,15 @@
 pub mod commands;
 
-use crate::error::Result;
+use crate::error::{Result, TaskError};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::io::Read;
 use std::path::Path;
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const SUBMODULE_MODE: &str = "160000";
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
@@ -16,6 +23,15 @@ pub enum DiffMode {
 pub struct DiffFile {
     pub path: String,
     pub status: String,
+    pub kind: DiffFileKind,
+}
+
+/// Submodule entries only record a change of the pinned commit, not file content.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffFileKind {
+    File,
+    Submodule,
 }
 
 #[derive(Debug, Serialize)]
@@ -64,34 +80,208 @@ pub fn git_diff(
     repo: &Path,
     base_commit: &str,
     ignore_whitespace: Option<&str>,
+    pathspecs: &[String],
 ) -> Result<DiffPayloadResult> {
     let mut diff_args = vec!["diff".to_string()];
     if let Some(flag) = ignore_whitespace {
         diff_args.push(flag.to_string());
     }
     diff_args.push(base_commit.to_string());
+    append_pathspecs(&mut diff_args, pathspecs);
     let diff = run_git(repo, diff_args)?;
 
-    let mut files_args = vec!["diff", "--name-status"].into_iter().map(|s| s.to_string()).collect::<Vec<String>>();
+    // `--raw` carries the file modes, which is how submodules (mode 160000) are told apart.
+    let mut files_args = vec!["diff", "--raw", "--no-abbrev"]
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>();
     if let Some(flag) = ignore_whitespace {
         files_args.push(flag.to_string());
     }
     files_args.push(base_commit.to_string());
+    append_pathspecs(&mut files_args, pathspecs);
     let files_output = run_git(repo, files_args)?;
-    let files = files_output
+    let files = files_output.lines().filter_map(parse_raw_diff_line).collect();
+
+    Ok(DiffPayloadResult { files, diff })
+}
+
+/// Parses a `git diff --raw` line such as `:100644 100644 <sha> <sha> M\tpath`.
+fn parse_raw_diff_line(line: &str) -> Option<DiffFile> {
+    let (meta, paths) = line.strip_prefix(':')?.split_once('\t')?;
+    let mut meta = meta.split_whitespace();
+    let old_mode = meta.next()?;
+    let new_mode = meta.next()?;
+    let status = meta.nth(2)?;
+    let path = paths.split('\t').next()?;
+    let kind = if old_mode == SUBMODULE_MODE || new_mode == SUBMODULE_MODE {
+        DiffFileKind::Submodule
+    } else {
+        DiffFileKind::File
+    };
+    Some(DiffFile {
+        path: path.to_string(),
+        status: status.to_string(),
+        kind,
+    })
+}
+
+fn append_pathspecs(args: &mut Vec<String>, pathspecs: &[String]) {
+    if !pathspecs.is_empty() {
+        args.push("--".to_string());
+        args.extend(pathspecs.iter().cloned());
+    }
+}
+
+/// Pathspecs covering a cone-mode sparse checkout: the listed directories plus
+/// files at the repository root, which cone mode always includes.
+pub fn sparse_pathspecs(sparse_paths: &[String]) -> Vec<String> {
+    if sparse_paths.is_empty() {
+        return Vec::new();
+    }
+    let mut pathspecs = sparse_paths.to_vec();
+    pathspecs.push(":(top,glob)*".to_string());
+    pathspecs
+}
+
+pub fn sparse_checkout_args(sparse_paths: &[String]) -> Vec<String> {
+    if sparse_paths.is_empty() {
+        return vec!["sparse-checkout".to_string(), "disable".to_string()];
+    }
+    let mut args = vec![
+        "sparse-checkout".to_string(),
+        "set".to_string(),
+        "--cone".to_string(),
+    ];
+    args.extend(sparse_paths.iter().cloned());
+    args
+}
+
+/// Paths of the submodules declared in the worktree's `.gitmodules`.
+pub fn submodule_paths(worktree: &Path) -> Result<Vec<String>> {
+    if !worktree.join(".gitmodules").is_file() {
+        return Ok(Vec::new());
+    }
+    // `--get-regexp` exits with 1 when nothing matches, which just means no submodules.
+    let output = run_git(
+        worktree,
+        [
+            "config",
+            "--file",
+            ".gitmodules",
+            "--get-regexp",
+            r"^submodule\..*\.path$",
+        ],
+    )
+    .unwrap_or_default();
+    Ok(output
         .lines()
-        .filter_map(|line| {
-            let mut parts = line.split_whitespace();
-            let status = parts.next()?;
-            let path = parts.next()?;
-            Some(DiffFile {
-                path: path.to_string(),
-                status: status.to_string(),
-            })
+        .filter_map(|line| line.split_once(' '))
+        .map(|(_, path)| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .collect())
+}
+
+/// Whether any `.gitattributes` committed at HEAD routes files through Git LFS.
+pub fn uses_lfs(worktree: &Path) -> bool {
+    run_git(
+        worktree,
+        ["grep", "-q", "filter=lfs", "HEAD", "--", ":(glob)**/.gitattributes"],
+    )
+    .is_ok()
+}
+
+pub fn lfs_installed(repo: &Path) -> bool {
+    run_git(repo, ["lfs", "version"]).is_ok()
+}
+
+/// Adds `pattern` to the repository's `info/exclude` unless it is already listed.
+pub fn ensure_excluded(repo: &Path, pattern: &str) -> Result<()> {
+    let output = run_git(repo, ["rev-parse", "--git-path", "info/exclude"])?;
+    let exclude_path = repo.join(output.trim());
+    let existing = std::fs::read_to_string(&exclude_path).unwrap_or_default();
+    if existing.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+    if let Some(parent) = exclude_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut contents = existing;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(pattern);
+    contents.push('\n');
+    std::fs::write(&exclude_path, contents)?;
+    Ok(())
+}
+
+pub fn merge_base(repo: &Path, left: &str, right: &str) -> Result<String> {
+    let output = run_git(repo, ["merge-base", left, right])?;
+    Ok(output.trim().to_string())
+}
+
+/// Best guess at the branch new work forks from: the remote's HEAD, then `main`/`master`.
+pub fn default_branch(repo: &Path) -> Option<String> {
+    if let Ok(output) = run_git(
+        repo,
+        ["symbolic-ref", "--quiet", "--short", "refs/remotes/origin/HEAD"],
+    ) {
+        let branch = output.trim();
+        if !branch.is_empty() {
+            return Some(branch.to_string());
+        }
+    }
+    ["main", "master"]
+        .into_iter()
+        .find(|candidate| {
+            ref_exists(repo, &format!("refs/heads/{}", candidate))
         })
-        .collect();
+        .map(|candidate| candidate.to_string())
+}
 
-    Ok(DiffPayloadResult { files, diff })
+/// Returns git's reason when `name` cannot be used as a branch name.
+pub fn check_branch_name(repo: &Path, name: &str) -> Option<String> {
+    match run_git(repo, ["check-ref-format", "--branch", name]) {
+        Ok(_) => None,
+        Err(err) => {
+            let reason = err.to_string();
+            let reason = reason.trim().trim_start_matches("fatal:").trim();
+            Some(if reason.is_empty() {
+                "rejected by git check-ref-format".to_string()
+            } else {
+                reason.to_string()
+            })
+        }
+    }
+}
+
+/// Short names of the refs under `prefix`, e.g. `refs/heads/feature/`.
+pub fn list_refs(repo: &Path, prefix: &str) -> Result<Vec<String>> {
+    let output = run_git(repo, ["for-each-ref", "--format=%(refname:short)", prefix])?;
+    Ok(output
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+pub fn ref_exists(repo: &Path, reference: &str) -> bool {
+    run_git(repo, ["rev-parse", "--verify", "--quiet", reference]).is_ok()
+}
+
+/// Remote-tracking branches named `<remote>/<branch>` for any remote.
+pub fn find_remote_branches(repo: &Path, branch: &str) -> Result<Vec<String>> {
+    let pattern = format!("refs/remotes/*/{}", branch);
+    let output = run_git(
+        repo,
+        ["for-each-ref", "--format=%(refname:short)", pattern.as_str()],
+    )?;
+    Ok(output
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
 }
 
 pub fn run_git<I, S>(repo: &Path, args: I) -> Result<String>
@@ -111,6 +301,102 @@ where
     }
 }
 
+/// Lets another thread abort a git process started by [`run_git_streaming`].
+#[derive(Clone, Default)]
+pub struct GitCancelHandle {
+    cancelled: Arc<AtomicBool>,
+    child: Arc<Mutex<Option<Child>>>,
+}
+
+impl GitCancelHandle {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if let Some(child) = self.child.lock().as_mut() {
+            let _ = child.kill();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Runs git while forwarding each stderr progress line (git separates updates
+/// with `\r`) to `on_progress`.
+pub fn run_git_streaming<I, S>(
+    repo: &Path,
+    args: I,
+    cancel: &GitCancelHandle,
+    mut on_progress: impl FnMut(&str),
+) -> Result<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    if cancel.is_cancelled() {
+        return Err(TaskError::Message("cancelled".into()));
+    }
+    let mut child = std::process::Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| TaskError::Message("failed to capture git output".into()))?;
+    *cancel.child.lock() = Some(child);
+    if cancel.is_cancelled() {
+        cancel.cancel();
+    }
+
+    // Only newline-terminated lines are kept for errors; `\r` marks transient progress.
+    let mut last_lines: Vec<String> = Vec::new();
+    let mut pending = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let size = match stderr.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(size) => size,
+        };
+        for &byte in &buffer[..size] {
+            if byte != b'\r' && byte != b'\n' {
+                pending.push(byte);
+                continue;
+            }
+            let line = String::from_utf8_lossy(&pending).trim().to_string();
+            pending.clear();
+            if line.is_empty() {
+                continue;
+            }
+            on_progress(&line);
+            if byte == b'\n' {
+                last_lines.push(line);
+            }
+        }
+    }
+    let line = String::from_utf8_lossy(&pending).trim().to_string();
+    if !line.is_empty() {
+        on_progress(&line);
+        last_lines.push(line);
+    }
+
+    let status = match cancel.child.lock().take() {
+        Some(mut child) => child.wait()?,
+        None => return Err(TaskError::Message("git process went missing".into())),
+    };
+    if cancel.is_cancelled() {
+        return Err(TaskError::Message("cancelled".into()));
+    }
+    if status.success() {
+        Ok(())
+    } else {
+        Err(TaskError::Message(last_lines.join("\n")))
+    }
+}
+
 pub fn get_repo_root(path: &Path) -> Result<std::path::PathBuf> {
     let output = run_git(path, ["rev-parse", "--show-toplevel"])?;
     Ok(std::path::PathBuf::from(output.trim()))
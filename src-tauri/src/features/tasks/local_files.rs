@@ -0,0 +1,114 @@
+use crate::error::{Result, TaskError};
+use crate::features::tasks::config::SetupConfig;
+use glob::MatchOptions;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy)]
+enum TransferMode {
+    Copy,
+    Symlink,
+}
+
+/// Brings ignored files such as `.env.local` from the base repo into a new
+/// worktree, reporting every file handled and every pattern that matched nothing.
+pub fn transfer_local_files(
+    repo_root: &Path,
+    worktree_path: &Path,
+    config: &SetupConfig,
+    mut on_progress: impl FnMut(String),
+) -> Result<()> {
+    let patterns = config
+        .copy
+        .iter()
+        .map(|pattern| (pattern, TransferMode::Copy))
+        .chain(
+            config
+                .symlink
+                .iter()
+                .map(|pattern| (pattern, TransferMode::Symlink)),
+        );
+    for (pattern, mode) in patterns {
+        let pattern = pattern.trim().trim_start_matches("./");
+        if pattern.is_empty() {
+            continue;
+        }
+        let sources = match_pattern(repo_root, pattern)?;
+        if sources.is_empty() {
+            on_progress(format!("warning: {} not found in base repo", pattern));
+            continue;
+        }
+        for source in sources {
+            let Ok(relative) = source.strip_prefix(repo_root) else {
+                continue;
+            };
+            let target = worktree_path.join(relative);
+            if target.exists() {
+                on_progress(format!(
+                    "warning: {} already exists in worktree, skipped",
+                    relative.display()
+                ));
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match mode {
+                TransferMode::Copy => {
+                    copy_recursive(&source, &target)?;
+                    on_progress(format!("copied {}", relative.display()));
+                }
+                TransferMode::Symlink => {
+                    symlink(&source, &target)?;
+                    on_progress(format!("linked {}", relative.display()));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn match_pattern(repo_root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let full_pattern = repo_root.join(pattern);
+    let options = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let paths = glob::glob_with(&full_pattern.to_string_lossy(), options)
+        .map_err(|err| TaskError::Message(format!("invalid pattern {}: {}", pattern, err)))?;
+    // Never reach into git metadata or illuc's own worktrees.
+    let excluded = [repo_root.join(".git"), repo_root.join(".illuc")];
+    Ok(paths
+        .flatten()
+        .filter(|path| !excluded.iter().any(|dir| path.starts_with(dir)))
+        .collect())
+}
+
+fn copy_recursive(source: &Path, target: &Path) -> Result<()> {
+    if source.is_dir() {
+        std::fs::create_dir_all(target)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+        }
+    } else {
+        std::fs::copy(source, target)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn symlink(source: &Path, target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(source, target)?;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn symlink(source: &Path, target: &Path) -> Result<()> {
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(source, target)?;
+    } else {
+        std::os::windows::fs::symlink_file(source, target)?;
+    }
+    Ok(())
+}
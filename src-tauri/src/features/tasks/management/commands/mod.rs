@@ -1,5 +1,8 @@
 pub mod select_base_repo;
+pub mod task_agent_sessions_list;
+pub mod task_approval_respond;
 pub mod task_create;
+pub mod task_create_cancel;
 pub mod task_discard;
 pub mod task_load_existing;
 pub mod task_open_worktree_in_vscode;
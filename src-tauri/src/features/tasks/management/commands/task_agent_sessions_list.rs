@@ -0,0 +1,24 @@
+use crate::commands::CommandResult;
+use crate::features::tasks::{AgentKind, AgentSession, TaskManager};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub task_id: Uuid,
+    /// Defaults to the task's current agent.
+    pub agent: Option<AgentKind>,
+}
+
+pub type Response = Vec<AgentSession>;
+
+#[tauri::command]
+pub async fn task_agent_sessions_list(
+    manager: tauri::State<'_, TaskManager>,
+    req: Request,
+) -> CommandResult<Response> {
+    manager
+        .list_agent_sessions(req)
+        .map_err(|err| err.to_string())
+}
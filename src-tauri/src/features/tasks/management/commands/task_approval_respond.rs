@@ -0,0 +1,23 @@
+use crate::commands::CommandResult;
+use crate::features::tasks::{ApprovalDecision, TaskManager};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub task_id: Uuid,
+    pub decision: ApprovalDecision,
+}
+
+pub type Response = ();
+
+#[tauri::command]
+pub async fn task_approval_respond(
+    manager: tauri::State<'_, TaskManager>,
+    req: Request,
+) -> CommandResult<Response> {
+    manager
+        .respond_to_approval(req)
+        .map_err(|err| err.to_string())
+}
@@ -1,6 +1,6 @@
-use crate::commands::CommandResult;
-use crate::features::tasks::{TaskManager, TaskSummary};
-use serde::Deserialize;
+use crate::error::TaskError;
+use crate::features::tasks::{AgentKind, BranchMode, BranchNameError, TaskManager, TaskSummary};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -8,18 +8,45 @@ pub struct Request {
     pub base_repo_path: String,
     pub task_title: Option<String>,
     pub base_ref: Option<String>,
+    /// Generated from the repo's branch template when omitted.
     pub branch_name: Option<String>,
+    pub branch_mode: Option<BranchMode>,
+    pub sparse_paths: Option<Vec<String>>,
+    /// When set, the agent starts with this prompt as soon as the worktree is ready.
+    pub prompt: Option<String>,
+    pub agent: Option<AgentKind>,
 }
 
 pub type Response = TaskSummary;
 
+/// Carries the branch problem alongside the message so the UI can point at the name.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Error {
+    pub message: String,
+    pub branch: Option<BranchNameError>,
+}
+
+impl From<TaskError> for Error {
+    fn from(err: TaskError) -> Self {
+        let branch = match &err {
+            TaskError::BranchName(branch) => Some(branch.clone()),
+            _ => None,
+        };
+        Self {
+            message: err.to_string(),
+            branch,
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn task_create(
     manager: tauri::State<'_, TaskManager>,
     app_handle: tauri::AppHandle,
     req: Request,
-) -> CommandResult<Response> {
+) -> Result<Response, Error> {
     manager
         .create_task(req, &app_handle)
-        .map_err(|err| err.to_string())
+        .map_err(Error::from)
 }
@@ -1,15 +1,31 @@
 use crate::commands::CommandResult;
-use crate::features::tasks::{AgentKind, TaskManager, TaskSummary};
+use crate::features::tasks::{
+    AgentBudgets, AgentKind, AgentLaunchOptions, RestartPolicy, TaskManager, TaskSummary,
+};
 use serde::Deserialize;
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
     pub task_id: Uuid,
     pub cols: Option<u16>,
     pub rows: Option<u16>,
     pub agent: Option<AgentKind>,
+    /// First instruction for the agent; defaults to the task's creation prompt on
+    /// its first start.
+    pub prompt: Option<String>,
+    /// Session to resume instead of the worktree's latest one.
+    pub session_id: Option<String>,
+    /// Start a fresh session even if the task has run before.
+    #[serde(default)]
+    pub new_session: bool,
+    /// Merged over the repo's `launchOptions` for the agent.
+    pub launch_options: Option<AgentLaunchOptions>,
+    /// Merged over the repo's `budgets`.
+    pub budgets: Option<AgentBudgets>,
+    /// Replaces the repo's `restartPolicy`.
+    pub restart_policy: Option<RestartPolicy>,
 }
 
 pub type Response = TaskSummary;
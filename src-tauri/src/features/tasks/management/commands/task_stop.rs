@@ -1,5 +1,5 @@
 use crate::commands::CommandResult;
-use crate::features::tasks::{TaskManager, TaskSummary};
+use crate::features::tasks::{StopOutcome, TaskManager};
 use serde::Deserialize;
 use uuid::Uuid;
 
@@ -7,9 +7,11 @@ use uuid::Uuid;
 #[serde(rename_all = "camelCase")]
 pub struct Request {
     pub task_id: Uuid,
+    /// Overrides the repo's `stopGracePeriodMs`.
+    pub grace_period_ms: Option<u64>,
 }
 
-pub type Response = TaskSummary;
+pub type Response = StopOutcome;
 
 #[tauri::command]
 pub async fn task_stop(
@@ -24,5 +24,6 @@ pub async fn task_terminal_resize(
         TerminalKind::Worktree => manager
             .worktree_terminal_resize(req)
             .map_err(|err| err.to_string()),
+        TerminalKind::Setup => Ok(()),
     }
 }
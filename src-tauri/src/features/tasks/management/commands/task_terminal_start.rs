@@ -25,5 +25,6 @@ pub async fn task_terminal_start(
         TerminalKind::Worktree => manager
             .start_worktree_terminal(req, &app_handle)
             .map_err(|err| err.to_string()),
+        TerminalKind::Setup => Ok(()),
     }
 }
@@ -23,5 +23,6 @@ pub async fn task_terminal_write(
         TerminalKind::Worktree => manager
             .worktree_terminal_write(req)
             .map_err(|err| err.to_string()),
+        TerminalKind::Setup => Err("The setup terminal is read-only.".to_string()),
     }
 }
@@ -1,9 +1,20 @@
 pub mod git;
 pub mod management;
 pub mod models;
+pub mod transcript;
 mod agents;
+mod approval_policy;
+mod branch_name;
+mod budget;
+mod config;
+mod creation;
 mod events;
+mod forbidden;
+mod local_files;
+mod pty;
 mod repo;
+mod setup;
+mod store;
 mod worktree;
 
 pub use git::commands::task_git_commit::Request as CommitTaskRequest;
@@ -11,7 +22,11 @@ pub use git::commands::task_git_diff_get::Request as DiffRequest;
 pub use git::commands::task_git_diff_watch_start::Request as StartDiffWatchRequest;
 pub use git::commands::task_git_diff_watch_stop::Request as StopDiffWatchRequest;
 pub use git::commands::task_git_push::Request as PushTaskRequest;
+pub use git::commands::task_git_sparse_set::Request as SetSparsePathsRequest;
+pub use management::commands::task_agent_sessions_list::Request as AgentSessionsListRequest;
+pub use management::commands::task_approval_respond::Request as ApprovalRespondRequest;
 pub use management::commands::task_create::Request as CreateTaskRequest;
+pub use management::commands::task_create_cancel::Request as CancelTaskCreationRequest;
 pub use management::commands::task_discard::Request as DiscardTaskRequest;
 pub use management::commands::task_open_worktree_in_vscode::Request as OpenWorktreeInVsCodeRequest;
 pub use management::commands::task_open_worktree_terminal::Request as OpenWorktreeTerminalRequest;
@@ -20,57 +35,124 @@ pub use management::commands::task_stop::Request as StopTaskRequest;
 pub use management::commands::task_terminal_resize::Request as TerminalResizeRequest;
 pub use management::commands::task_terminal_write::Request as TerminalWriteRequest;
 pub use management::commands::task_terminal_start::Request as StartWorktreeTerminalRequest;
-pub use models::{AgentKind, BaseRepoInfo, DiffPayload, TaskStatus, TaskSummary};
+pub use transcript::commands::task_transcript_get::Request as TranscriptGetRequest;
+pub use transcript::commands::task_transcript_watch_start::Request as StartTranscriptWatchRequest;
+pub use transcript::commands::task_transcript_watch_stop::Request as StopTranscriptWatchRequest;
+pub use models::{
+    AgentBudgets, AgentFailure, AgentKind, AgentLaunchOptions, AgentSession, ApprovalDecision,
+    BaseRepoInfo, BranchMode, Budget, DiffPayload, FailureReason, RestartPolicy, SandboxMode,
+    StopOutcome, StopReason, TaskHistoryEntry, TaskHistoryEvent, TaskStatus, TaskSummary,
+};
 pub use models::TerminalKind;
+pub use branch_name::BranchNameError;
 pub use repo::handle_select_base_repo;
 
-use crate::features::tasks::agents::{Agent, AgentCallbacks, AgentRuntime, ChildHandle};
-use crate::features::tasks::agents::codex::CodexAgent;
-use crate::features::tasks::agents::copilot::CopilotAgent;
+use crate::features::tasks::agents::{Agent, AgentCallbacks, AgentStartOptions};
+use crate::features::tasks::agents::aider::AiderAgent;
+use crate::features::tasks::agents::codex::{self, CodexAgent};
+use crate::features::tasks::agents::copilot::{self, CopilotAgent};
+use crate::features::tasks::agents::custom::CustomAgent;
+use crate::features::tasks::agents::failure::{classify_spawn_error, failure_reason};
 use crate::error::{Result, TaskError};
 use crate::features::launcher;
 use crate::features::tasks::git::{
-    git_commit, git_diff, git_push, get_repo_root, list_worktrees, run_git, validate_git_repo,
+    default_branch, git_commit, git_diff, git_push, get_repo_root, list_worktrees, merge_base,
+    run_git, sparse_checkout_args, sparse_pathspecs, validate_git_repo,
+    GitCancelHandle,
+};
+use approval_policy::ApprovalPolicy;
+use branch_name::{render_branch_template, validate_new_branch};
+use budget::BudgetClock;
+use config::{find_agent_definition, load_repo_config};
+use forbidden::load_forbidden_rules;
+use pty::{ChildHandle, PtyHandlers, PtyProcess};
+use events::{
+    emit_approval_requested, emit_budget_exceeded, emit_diff_changed, emit_status,
+    emit_terminal_exit, emit_terminal_output,
 };
-use events::{emit_diff_changed, emit_status, emit_terminal_exit, emit_terminal_output};
 use crate::utils::fs::ensure_directory;
 use crate::utils::path::normalize_path_string;
+use crate::utils::process::{group_alive, interrupt_group, kill_group};
 use chrono::Utc;
 use log::{debug, info, warn};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::{Mutex, RwLock};
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
-use std::collections::HashMap;
-use std::io::{Read, Write};
+use portable_pty::{CommandBuilder, PtySize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use store::{load_tasks, queue_save, repo_key, StoredTask};
 use tauri::AppHandle;
 use uuid::Uuid;
-use worktree::{clean_branch_name, format_title_from_branch, managed_worktree_root};
+use creation::WorktreeCreation;
+use transcript::{load_transcript, Transcript, TranscriptWatcher};
+use worktree::{
+    clean_branch_name, default_worktree_root, ensure_branch_available, format_title_from_branch,
+    managed_worktree_root, normalize_sparse_paths, resolve_existing_branch, BranchCheckout,
+};
 
 
 const DEFAULT_SCREEN_ROWS: usize = 40;
 const DEFAULT_SCREEN_COLS: usize = 120;
 const DEFAULT_PTY_ROWS: u16 = 40;
 const DEFAULT_PTY_COLS: u16 = 80;
-
-type MasterHandle = Arc<Mutex<Box<dyn portable_pty::MasterPty + Send>>>;
-
-type WriteHandle = Arc<Mutex<Box<dyn Write + Send>>>;
+const APPROVAL_SETTLE_DELAY: Duration = Duration::from_millis(300);
+const MAX_HISTORY_ENTRIES: usize = 200;
+const DEFAULT_STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+/// How long a killed process group gets to disappear before the stop reports failure.
+const KILL_WAIT: Duration = Duration::from_secs(2);
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Longest wait before a restart, however often the backoff has doubled.
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(300);
 
 pub use git::DiffMode;
 
-fn build_agent(agent_kind: AgentKind) -> Box<dyn Agent> {
-    match agent_kind {
+/// Configured agents are looked up on every start so config edits apply to the next run.
+fn build_agent(agent_kind: &AgentKind, repo_root: &Path) -> Result<Box<dyn Agent>> {
+    Ok(match agent_kind {
         AgentKind::Codex => Box::new(CodexAgent::default()),
         AgentKind::Copilot => Box::new(CopilotAgent::default()),
-    }
+        AgentKind::Aider => Box::new(AiderAgent::default()),
+        AgentKind::Custom(id) => {
+            let definition = find_agent_definition(repo_root, id)?;
+            Box::new(CustomAgent::new(definition)?)
+        }
+    })
 }
 
-fn agent_label(agent_kind: AgentKind) -> &'static str {
+fn agent_label(agent_kind: &AgentKind) -> &str {
     match agent_kind {
         AgentKind::Codex => "Codex",
         AgentKind::Copilot => "Copilot CLI",
+        AgentKind::Aider => "Aider",
+        AgentKind::Custom(id) => id,
+    }
+}
+
+/// Polls until the agent and every process in its group are gone, or `timeout` passes.
+/// Gives `None` once the agent has exited where its group cannot be observed. The exit
+/// poller may reap the agent first; its status stays readable afterwards.
+fn wait_for_exit(
+    child: &Mutex<ChildHandle>,
+    pid: Option<u32>,
+    timeout: Duration,
+) -> Option<bool> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let agent_exited = !matches!(child.lock().try_wait(), Ok(None));
+        if agent_exited {
+            match pid.map_or(Some(false), group_alive) {
+                Some(false) => return Some(true),
+                None => return None,
+                Some(true) => {}
+            }
+        }
+        if Instant::now() >= deadline {
+            return Some(false);
+        }
+        std::thread::sleep(EXIT_POLL_INTERVAL);
     }
 }
 
@@ -97,17 +179,24 @@ fn build_worktree_shell_command(worktree_path: &Path) -> CommandBuilder {
 
 
 struct TaskRecord {
-    agent: Box<dyn Agent>,
+    /// Built when the agent starts.
+    agent: Option<Box<dyn Agent>>,
     agent_kind: AgentKind,
     summary: TaskSummary,
-    runtime: Option<TaskRuntime>,
-    shell: Option<TaskRuntime>,
+    runtime: Option<PtyProcess>,
+    /// Running while the agent is.
+    budget_clock: Option<BudgetClock>,
+    /// Set by the agent's latest start.
+    restart: Option<RestartPlan>,
+    shell: Option<PtyProcess>,
+    creation: Option<GitCancelHandle>,
 }
 
-struct TaskRuntime {
-    child: Arc<Mutex<ChildHandle>>,
-    writer: WriteHandle,
-    master: MasterHandle,
+/// How to bring the agent back should it exit with an error.
+struct RestartPlan {
+    policy: RestartPolicy,
+    /// The latest start, minus its prompt, resuming the session it began.
+    request: StartTaskRequest,
 }
 
 struct DiffWatcher {
@@ -122,6 +211,8 @@ pub struct TaskManager {
 struct TaskManagerInner {
     tasks: RwLock<HashMap<Uuid, TaskRecord>>,
     diff_watchers: Mutex<HashMap<Uuid, DiffWatcher>>,
+    transcript_watchers: Mutex<HashMap<Uuid, TranscriptWatcher>>,
+    loaded_repos: Mutex<HashSet<String>>,
 }
 
 impl Default for TaskManagerInner {
@@ -129,6 +220,8 @@ impl Default for TaskManagerInner {
         Self {
             tasks: RwLock::new(HashMap::new()),
             diff_watchers: Mutex::new(HashMap::new()),
+            transcript_watchers: Mutex::new(HashMap::new()),
+            loaded_repos: Mutex::new(HashSet::new()),
         }
     }
 }
@@ -144,25 +237,67 @@ impl TaskManager {
             task_title,
             base_ref,
             branch_name,
+            branch_mode,
+            sparse_paths,
+            prompt,
+            agent,
         } = req;
+        let prompt = prompt
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
 
         let base_repo = PathBuf::from(base_repo_path);
         let repo_root = get_repo_root(&base_repo)?;
         ensure_directory(&base_repo)?;
 
         validate_git_repo(&base_repo)?;
+        self.ensure_tasks_loaded(&repo_root, app)?;
 
         let base_ref = base_ref.unwrap_or_else(|| "HEAD".to_string());
-        let base_commit = run_git(&repo_root, ["rev-parse", base_ref.as_str()])?;
+        let base_commit = run_git(&repo_root, ["rev-parse", base_ref.as_str()])?
+            .trim()
+            .to_string();
+        // Record a name that still means the same thing after the base repo switches branches.
+        let base_branch = if base_ref == "HEAD" {
+            run_git(&repo_root, ["rev-parse", "--abbrev-ref", "HEAD"])
+                .map(|output| output.trim().to_string())
+                .unwrap_or_else(|_| base_commit.clone())
+        } else {
+            base_ref.clone()
+        };
 
         let task_id = Uuid::new_v4();
         let title = task_title.unwrap_or_else(|| format!("Task {}", task_id.simple()));
         let timestamp = Utc::now();
         let branch_name = branch_name
-            .map(|value| value.trim().to_string())
-            .filter(|value| !value.is_empty())
-            .ok_or_else(|| TaskError::Message("Branch name is required.".into()))?;
-        info!("create_task task_id={} branch={}", task_id, branch_name);
+            .map(|value| clean_branch_name(&value))
+            .filter(|value| !value.is_empty());
+        let sparse_paths = normalize_sparse_paths(sparse_paths.unwrap_or_default())?;
+        let branch_mode = branch_mode.unwrap_or_default();
+        let checkout = match branch_mode {
+            BranchMode::New => {
+                let branch = match branch_name {
+                    Some(name) => name,
+                    None => match load_repo_config(&repo_root)?.branch_template {
+                        Some(template) => render_branch_template(&repo_root, &template, &title)?,
+                        None => return Err(BranchNameError::Missing.into()),
+                    },
+                };
+                validate_new_branch(&repo_root, &branch)?;
+                BranchCheckout::New { branch, base_ref }
+            }
+            BranchMode::Existing => {
+                let branch_name = branch_name.ok_or(BranchNameError::Missing)?;
+                let checkout = resolve_existing_branch(&repo_root, &branch_name)?;
+                ensure_branch_available(&repo_root, checkout.local_name())?;
+                checkout
+            }
+        };
+        let branch_name = checkout.local_name().to_string();
+        info!(
+            "create_task task_id={} branch={} mode={:?}",
+            task_id, branch_name, branch_mode
+        );
 
         let managed_root = managed_worktree_root(&repo_root)?;
         let worktree_path = managed_root.join(task_id.to_string());
@@ -171,61 +306,115 @@ impl TaskManager {
             std::fs::remove_dir_all(&worktree_path).ok();
         }
 
-        let worktree_path_str = worktree_path.to_string_lossy().to_string();
-        let worktree_path_display = normalize_path_string(&worktree_path);
-        run_git(
-            &repo_root,
-            [
-                "worktree",
-                "add",
-                "-b",
-                branch_name.as_str(),
-                worktree_path_str.as_str(),
-                base_ref.as_str(),
-            ],
-        )?;
+        // The worktree does not exist yet, so resolve the display path via its parent.
+        let worktree_path_display =
+            normalize_path_string(&Path::new(&repo_key(&managed_root)).join(task_id.to_string()));
 
         let summary = TaskSummary {
             task_id,
             title,
-            status: TaskStatus::Stopped,
+            status: TaskStatus::CreatingWorktree,
             created_at: timestamp,
             started_at: None,
             ended_at: None,
             worktree_path: worktree_path_display,
             branch_name,
-            base_branch: base_ref.clone(),
-            base_repo_path: normalize_path_string(&repo_root),
+            branch_mode,
+            base_branch: base_branch.clone(),
+            base_repo_path: repo_key(&repo_root),
             base_commit,
+            sparse_paths: sparse_paths.clone(),
+            prompt,
             exit_code: None,
+            failure_reason: None,
+            launch_options: None,
+            budgets: None,
+            stop_reason: None,
+            restart_attempts: 0,
+            history: Vec::new(),
         };
 
+        let cancel = GitCancelHandle::default();
         let mut tasks = self.inner.tasks.write();
         tasks.insert(
             task_id,
             TaskRecord {
-                agent: build_agent(AgentKind::Codex),
-                agent_kind: AgentKind::Codex,
+                agent: None,
+                agent_kind: agent.unwrap_or(AgentKind::Codex),
                 summary: summary.clone(),
                 runtime: None,
+                budget_clock: None,
+                restart: None,
                 shell: None,
+                creation: Some(cancel.clone()),
             },
         );
+        self.publish_task(&tasks, task_id, app);
         drop(tasks);
-        emit_status(app, &summary);
+
+        let creation = WorktreeCreation {
+            task_id,
+            repo_root,
+            worktree_path,
+            checkout,
+            base_branch,
+            sparse_paths,
+        };
+        let manager = self.clone();
+        let creation_app = app.clone();
+        std::thread::spawn(move || {
+            manager.run_worktree_creation(creation, cancel, &creation_app);
+        });
         Ok(summary)
     }
 
+    pub fn cancel_task_creation(&self, req: CancelTaskCreationRequest) -> Result<()> {
+        let task_id = req.task_id;
+        info!("cancel_task_creation task_id={}", task_id);
+        let tasks = self.inner.tasks.read();
+        let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+        match &record.creation {
+            Some(cancel) => {
+                cancel.cancel();
+                Ok(())
+            }
+            None => Err(TaskError::Message(
+                "Task worktree is not being created.".into(),
+            )),
+        }
+    }
+
     pub fn start_task(
         &self,
         req: StartTaskRequest,
         app: &AppHandle,
     ) -> Result<TaskSummary> {
+        self.start_agent(req, 0, app)
+    }
+
+    /// Starts the agent, `restart_attempts` being the restarts that led up to this one.
+    fn start_agent(
+        &self,
+        req: StartTaskRequest,
+        restart_attempts: u32,
+        app: &AppHandle,
+    ) -> Result<TaskSummary> {
+        let replay = StartTaskRequest {
+            prompt: None,
+            new_session: false,
+            ..req.clone()
+        };
         let StartTaskRequest {
             task_id,
             cols,
             rows,
             agent,
+            prompt,
+            session_id,
+            new_session,
+            launch_options,
+            budgets,
+            restart_policy,
         } = req;
         let requested_rows = rows.filter(|value| *value > 0);
         let requested_cols = cols.filter(|value| *value > 0);
@@ -243,23 +432,54 @@ impl TaskManager {
             if record.runtime.is_some() {
                 return Err(TaskError::AlreadyRunning);
             }
+            if record.creation.is_some() {
+                return Err(TaskError::Message(
+                    "Task worktree is still being created.".into(),
+                ));
+            }
         }
 
-        let (worktree_path, title, _has_started) = {
+        let (worktree_path, title, mut options, base_repo_path) = {
             let tasks = self.inner.tasks.read();
             let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+            // Only the first run starts a new session, carrying the creation prompt.
+            let has_started = record.summary.started_at.is_some();
+            let prompt = prompt
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .or_else(|| {
+                    if has_started {
+                        None
+                    } else {
+                        record.summary.prompt.clone()
+                    }
+                });
             (
                 PathBuf::from(&record.summary.worktree_path),
                 record.summary.title.clone(),
-                record.summary.started_at.is_some(),
+                AgentStartOptions {
+                    prompt,
+                    resume: has_started && !new_session,
+                    session_id: session_id.filter(|value| !new_session && !value.is_empty()),
+                    forbidden: Vec::new(),
+                    launch: AgentLaunchOptions::default(),
+                },
+                PathBuf::from(&record.summary.base_repo_path),
             )
         };
+        options.forbidden = load_forbidden_rules(&base_repo_path)?;
+        let repo_config = load_repo_config(&base_repo_path)?;
+        let mut launch_defaults = repo_config.launch_options;
+        let budgets = budgets.unwrap_or_default().with_defaults(&repo_config.budgets);
+        let restart_policy = restart_policy.unwrap_or(repo_config.restart_policy);
         info!("start_task task_id={} title={}", task_id, title);
 
         let status_manager = self.clone();
         let status_app = app.clone();
         let output_manager = self.clone();
         let output_app = app.clone();
+        let tick_manager = self.clone();
+        let tick_app = app.clone();
         let exit_manager = self.clone();
         let exit_app = app.clone();
         let callbacks = AgentCallbacks {
@@ -269,34 +489,55 @@ impl TaskManager {
             on_status: Arc::new(move |status: TaskStatus| {
                 status_manager.handle_agent_status(task_id, status, &status_app);
             }),
+            on_tick: Arc::new(move |now: Instant| {
+                tick_manager.handle_agent_tick(task_id, now, &tick_app);
+            }),
             on_exit: Arc::new(move |exit_code: i32| {
                 exit_manager.handle_agent_exit(task_id, exit_code, &exit_app);
             }),
         };
 
-        let agent_runtime = {
+        let (process, agent_kind, unenforced) = {
             let mut tasks = self.inner.tasks.write();
             let record = tasks
                 .get_mut(&task_id)
                 .ok_or(TaskError::NotFound)?;
             if let Some(requested_agent) = agent {
                 record.agent_kind = requested_agent;
-                record.agent = build_agent(requested_agent);
             }
-            let label = agent_label(record.agent_kind);
-            record.agent.reset(screen_rows, screen_cols);
-            record
-                .agent
-                .start(&worktree_path, callbacks, pty_rows, pty_cols)
-                .with_context(|| format!("failed to start {} for task {}", label, title))?
+            let mut agent = build_agent(&record.agent_kind, &base_repo_path)?;
+            let defaults = launch_defaults
+                .remove(record.agent_kind.id())
+                .unwrap_or_default();
+            options.launch = launch_options.unwrap_or_default().with_defaults(&defaults);
+            agent
+                .launch_args(&options.launch)
+                .map_err(|err| TaskError::Message(err.to_string()))?;
+            agent.reset(screen_rows, screen_cols);
+            let process =
+                match agent.start(&worktree_path, &options, callbacks, pty_rows, pty_cols) {
+                    Ok(process) => process,
+                    Err(err) => {
+                        warn!("agent failed to start task_id={} err={:#}", task_id, err);
+                        let reason =
+                            failure_reason(classify_spawn_error(&err), &record.agent_kind);
+                        let message = format!(
+                            "failed to start {} for task {}: {:#}. {}",
+                            agent_label(&record.agent_kind),
+                            title,
+                            err,
+                            reason.hint
+                        );
+                        record.summary.failure_reason = Some(reason);
+                        self.publish_task(&tasks, task_id, app);
+                        return Err(TaskError::Message(message));
+                    }
+                };
+            let unenforced = agent.enforce(&options.forbidden).unenforced;
+            record.agent = Some(agent);
+            (process, record.agent_kind.clone(), unenforced)
         };
 
-        let AgentRuntime {
-            child,
-            writer,
-            master,
-        } = agent_runtime;
-
         {
             let mut tasks = self.inner.tasks.write();
             let record = tasks
@@ -305,12 +546,32 @@ impl TaskManager {
             record.summary.status = TaskStatus::Idle;
             record.summary.started_at = Some(Utc::now());
             record.summary.exit_code = None;
-            record.runtime = Some(TaskRuntime {
-                child: child.clone(),
-                writer: writer.clone(),
-                master: master.clone(),
+            record.summary.failure_reason = None;
+            record.summary.launch_options = Some(options.launch.clone());
+            record.summary.budgets = Some(budgets.clone());
+            record.summary.stop_reason = None;
+            record.summary.restart_attempts = restart_attempts;
+            record.runtime = Some(process);
+            record.budget_clock = Some(BudgetClock::new(budgets, Instant::now()));
+            record.restart = Some(RestartPlan {
+                policy: restart_policy,
+                request: replay,
             });
-            emit_status(app, &record.summary);
+            self.publish_task(&tasks, task_id, app);
+        }
+
+        if !unenforced.is_empty() {
+            warn!(
+                "forbidden commands not enforced task_id={} agent={} rules={}",
+                task_id,
+                agent_kind.id(),
+                unenforced.join(", ")
+            );
+            let event = TaskHistoryEvent::UnenforcedRules {
+                agent: agent_kind,
+                rules: unenforced,
+            };
+            self.record_history(task_id, event, app);
         }
 
         let tasks = self.inner.tasks.read();
@@ -318,48 +579,130 @@ impl TaskManager {
         Ok(record.summary.clone())
     }
 
-    pub fn stop_task(
-        &self,
-        req: StopTaskRequest,
-        app: &AppHandle,
-    ) -> Result<TaskSummary> {
-        let task_id = req.task_id;
-        info!("stop_task task_id={}", task_id);
-        let child = {
+    /// Sessions the agent has recorded for the task's worktree, most recent first.
+    /// Only Codex and Copilot keep sessions that can be resumed by id.
+    pub fn list_agent_sessions(&self, req: AgentSessionsListRequest) -> Result<Vec<AgentSession>> {
+        let (worktree_path, agent_kind) = {
             let tasks = self.inner.tasks.read();
-            let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
-            if let Some(runtime) = &record.runtime {
-                runtime.child.clone()
-            } else {
-                return Err(TaskError::NotRunning);
-            }
+            let record = tasks.get(&req.task_id).ok_or(TaskError::NotFound)?;
+            (
+                PathBuf::from(&record.summary.worktree_path),
+                req.agent.unwrap_or_else(|| record.agent_kind.clone()),
+            )
+        };
+        let sessions = match agent_kind {
+            AgentKind::Codex => codex::list_sessions(&worktree_path)?,
+            AgentKind::Copilot => copilot::list_sessions(&worktree_path)?,
+            AgentKind::Aider | AgentKind::Custom(_) => Vec::new(),
         };
+        Ok(sessions)
+    }
 
-        if let Some(mut child_guard) = child.try_lock() {
-            let _ = child_guard.kill();
-        }
+    /// Interrupts the agent with Ctrl-C and SIGINT, and kills its whole process group if
+    /// anything is still running once the grace period is over.
+    pub fn stop_task(&self, req: StopTaskRequest, app: &AppHandle) -> Result<StopOutcome> {
+        self.stop_agent(req.task_id, req.grace_period_ms, StopReason::User, app)
+    }
 
-        {
+    fn stop_agent(
+        &self,
+        task_id: Uuid,
+        grace_period_ms: Option<u64>,
+        reason: StopReason,
+        app: &AppHandle,
+    ) -> Result<StopOutcome> {
+        info!("stop_task task_id={} reason={:?}", task_id, reason);
+        let (child, writer, base_repo_path) = {
             let mut tasks = self.inner.tasks.write();
             let record = tasks
                 .get_mut(&task_id)
                 .ok_or(TaskError::NotFound)?;
+            let Some(runtime) = &record.runtime else {
+                return Err(TaskError::NotRunning);
+            };
+            let handles = (
+                runtime.child.clone(),
+                runtime.writer.clone(),
+                PathBuf::from(&record.summary.base_repo_path),
+            );
+            // Marked first so the exit the interrupt causes is not taken for a failure.
             record.summary.status = TaskStatus::Stopped;
-            emit_status(app, &record.summary);
-            return Ok(record.summary.clone());
+            record.summary.stop_reason = Some(reason);
+            self.publish_task(&tasks, task_id, app);
+            handles
+        };
+        let grace_period = grace_period_ms
+            .or_else(|| {
+                load_repo_config(&base_repo_path)
+                    .ok()
+                    .and_then(|config| config.stop_grace_period_ms)
+            })
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_STOP_GRACE_PERIOD);
+
+        let pid = child.lock().process_id();
+        {
+            let mut writer = writer.lock();
+            let _ = writer.write_all(b"\x03").and_then(|_| writer.flush());
         }
+        if let Some(pid) = pid {
+            interrupt_group(pid);
+        }
+        let mut exited = wait_for_exit(&child, pid, grace_period);
+        let graceful = exited != Some(false);
+        if !graceful {
+            if let Some(pid) = pid {
+                kill_group(pid);
+            }
+            let _ = child.lock().kill();
+            exited = wait_for_exit(&child, pid, KILL_WAIT);
+        }
+        match exited {
+            Some(true) => info!("stop_task task_id={} graceful={}", task_id, graceful),
+            Some(false) => warn!("stop_task task_id={} agent processes still running", task_id),
+            None => info!(
+                "stop_task task_id={} graceful={} child processes unknown",
+                task_id, graceful
+            ),
+        }
+
+        let tasks = self.inner.tasks.read();
+        let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+        Ok(StopOutcome {
+            task: record.summary.clone(),
+            graceful,
+            exited,
+        })
     }
 
     pub fn discard_task(&self, req: DiscardTaskRequest, app: &AppHandle) -> Result<()> {
         let task_id = req.task_id;
         info!("discard_task task_id={}", task_id);
         self.remove_diff_watch(task_id);
-        let (worktree_path, branch_name, base_repo_path, runtime_exists, shell_exists) = {
+        self.inner.transcript_watchers.lock().remove(&task_id);
+        {
+            let tasks = self.inner.tasks.read();
+            let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+            // The creation thread removes the task once git has been stopped.
+            if let Some(cancel) = &record.creation {
+                cancel.cancel();
+                return Ok(());
+            }
+        }
+        let (
+            worktree_path,
+            branch_name,
+            branch_mode,
+            base_repo_path,
+            runtime_exists,
+            shell_exists,
+        ) = {
             let tasks = self.inner.tasks.read();
             let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
             (
                 PathBuf::from(&record.summary.worktree_path),
                 record.summary.branch_name.clone(),
+                record.summary.branch_mode,
                 PathBuf::from(&record.summary.base_repo_path),
                 record.runtime.is_some(),
                 record.shell.is_some(),
@@ -367,15 +710,18 @@ impl TaskManager {
         };
 
         if runtime_exists {
-            let _ = self.stop_task(StopTaskRequest { task_id }, app);
+            let stop = StopTaskRequest {
+                task_id,
+                grace_period_ms: None,
+            };
+            let _ = self.stop_task(stop, app);
         }
         if shell_exists {
             let mut tasks = self.inner.tasks.write();
             if let Some(record) = tasks.get_mut(&task_id) {
                 if let Some(shell) = record.shell.take() {
-                    if let Some(mut child_guard) = shell.child.try_lock() {
-                        let _ = child_guard.kill();
-                    }
+                    // The supervisor only holds the child's lock while checking for exit.
+                    let _ = shell.child.lock().kill();
                 }
             }
         }
@@ -390,7 +736,9 @@ impl TaskManager {
                 worktree_path_string.as_str(),
             ],
         );
-        let _ = run_git(&base_repo_path, ["branch", "-D", branch_name.as_str()]);
+        if branch_mode == BranchMode::New {
+            let _ = run_git(&base_repo_path, ["branch", "-D", branch_name.as_str()]);
+        }
         if worktree_path.exists() {
             let _ = std::fs::remove_dir_all(&worktree_path);
         }
@@ -405,7 +753,9 @@ impl TaskManager {
         }
 
         let mut tasks = self.inner.tasks.write();
-        tasks.remove(&task_id);
+        if let Some(record) = tasks.remove(&task_id) {
+            self.persist_tasks(&tasks, &record.summary.base_repo_path);
+        }
         Ok(())
     }
 
@@ -428,6 +778,48 @@ impl TaskManager {
         Ok(())
     }
 
+    pub fn respond_to_approval(&self, req: ApprovalRespondRequest) -> Result<()> {
+        info!("respond_to_approval task_id={} decision={:?}", req.task_id, req.decision);
+        self.answer_approval(req.task_id, req.decision, None).map(|_| ())
+    }
+
+    /// Types the keys for `decision`. With `command` set, the prompt is only answered
+    /// while it still asks for that command, checked under the same lock the keys are
+    /// written in; `Ok(false)` means the agent has moved on to another prompt.
+    fn answer_approval(
+        &self,
+        task_id: Uuid,
+        decision: ApprovalDecision,
+        command: Option<&str>,
+    ) -> Result<bool> {
+        let tasks = self.inner.tasks.read();
+        let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+        let (Some(runtime), Some(agent)) = (&record.runtime, &record.agent) else {
+            return Err(TaskError::NotRunning);
+        };
+        if record.summary.status != TaskStatus::AwaitingApproval {
+            return Err(TaskError::Message(
+                "Task is not waiting for approval.".into(),
+            ));
+        }
+        if command.is_some_and(|command| agent.pending_approval().as_deref() != Some(command)) {
+            return Ok(false);
+        }
+        let keys = agent.approval_keys(decision).ok_or_else(|| {
+            TaskError::Message(format!(
+                "{} cannot answer {:?} to this prompt.",
+                agent_label(&record.agent_kind),
+                decision
+            ))
+        })?;
+        let mut writer_guard = runtime.writer.lock();
+        writer_guard
+            .write_all(keys.as_bytes())
+            .with_context(|| "failed to write to terminal")?;
+        writer_guard.flush().ok();
+        Ok(true)
+    }
+
     pub fn terminal_resize(&self, req: TerminalResizeRequest) -> Result<()> {
         let task_id = req.task_id;
         debug!("terminal_resize task_id={} rows={} cols={}", task_id, req.rows, req.cols);
@@ -450,10 +842,11 @@ impl TaskManager {
             .with_context(|| "failed to resize terminal")?;
         {
             let mut tasks = self.inner.tasks.write();
-            if let Some(record) = tasks.get_mut(&task_id) {
-                record
-                    .agent
-                    .resize(req.rows as usize, req.cols as usize);
+            if let Some(agent) = tasks
+                .get_mut(&task_id)
+                .and_then(|record| record.agent.as_mut())
+            {
+                agent.resize(req.rows as usize, req.cols as usize);
             }
         }
         Ok(())
@@ -542,16 +935,22 @@ impl TaskManager {
     pub fn get_diff(&self, req: DiffRequest) -> Result<DiffPayload> {
         let task_id = req.task_id;
         debug!("get_diff task_id={} mode={:?}", task_id, req.mode);
-        let (worktree_path, base_commit) = {
+        let (worktree_path, base_commit, pathspecs) = {
             let tasks = self.inner.tasks.read();
             let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
             (
                 PathBuf::from(&record.summary.worktree_path),
                 record.summary.base_commit.clone(),
+                sparse_pathspecs(&record.summary.sparse_paths),
             )
         };
 
-        let _ = run_git(worktree_path.as_path(), ["add", "-A"]);
+        let mut add_args = vec!["add".to_string(), "-A".to_string()];
+        if !pathspecs.is_empty() {
+            add_args.push("--".to_string());
+            add_args.extend(pathspecs.iter().cloned());
+        }
+        let _ = run_git(worktree_path.as_path(), add_args);
 
         let whitespace_flag = if req.ignore_whitespace.unwrap_or(false) {
             Some("--ignore-all-space")
@@ -561,7 +960,8 @@ impl TaskManager {
         let mode = req.mode.unwrap_or(DiffMode::Worktree);
         match mode {
             DiffMode::Worktree => {
-                let combined = git_diff(worktree_path.as_path(), "HEAD", whitespace_flag)?;
+                let combined =
+                    git_diff(worktree_path.as_path(), "HEAD", whitespace_flag, &pathspecs)?;
                 Ok(DiffPayload {
                     task_id,
                     files: combined.files,
@@ -569,8 +969,12 @@ impl TaskManager {
                 })
             }
             DiffMode::Branch => {
-                let branch_diff =
-                    git_diff(worktree_path.as_path(), base_commit.as_str(), whitespace_flag)?;
+                let branch_diff = git_diff(
+                    worktree_path.as_path(),
+                    base_commit.as_str(),
+                    whitespace_flag,
+                    &pathspecs,
+                )?;
                 Ok(DiffPayload {
                     task_id,
                     files: branch_diff.files,
@@ -582,21 +986,113 @@ impl TaskManager {
 
     pub fn start_diff_watch(&self, req: StartDiffWatchRequest, app: &AppHandle) -> Result<()> {
         let task_id = req.task_id;
-        let worktree_path = self.worktree_path(task_id)?;
+        let (worktree_path, sparse_paths) = {
+            let tasks = self.inner.tasks.read();
+            let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+            (
+                PathBuf::from(&record.summary.worktree_path),
+                record.summary.sparse_paths.clone(),
+            )
+        };
         let mut watchers = self.inner.diff_watchers.lock();
         if watchers.contains_key(&task_id) {
             return Ok(());
         }
-        let watcher = DiffWatcher::new(task_id, worktree_path, app.clone())?;
+        let watcher = DiffWatcher::new(task_id, worktree_path, sparse_paths, app.clone())?;
         watchers.insert(task_id, watcher);
         Ok(())
     }
 
+    pub fn set_sparse_paths(
+        &self,
+        req: SetSparsePathsRequest,
+        app: &AppHandle,
+    ) -> Result<TaskSummary> {
+        let task_id = req.task_id;
+        let sparse_paths = normalize_sparse_paths(req.paths)?;
+        info!("set_sparse_paths task_id={} paths={:?}", task_id, sparse_paths);
+        let worktree_path = {
+            let tasks = self.inner.tasks.read();
+            let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+            if record.creation.is_some() {
+                return Err(TaskError::Message(
+                    "Task worktree is still being created.".into(),
+                ));
+            }
+            PathBuf::from(&record.summary.worktree_path)
+        };
+        run_git(&worktree_path, sparse_checkout_args(&sparse_paths))?;
+
+        let summary = {
+            let mut tasks = self.inner.tasks.write();
+            let record = tasks.get_mut(&task_id).ok_or(TaskError::NotFound)?;
+            record.summary.sparse_paths = sparse_paths.clone();
+            let summary = record.summary.clone();
+            self.publish_task(&tasks, task_id, app);
+            summary
+        };
+
+        // A running watcher filters on the old sparse set; rebuild it.
+        let mut watchers = self.inner.diff_watchers.lock();
+        if watchers.remove(&task_id).is_some() {
+            let watcher = DiffWatcher::new(task_id, worktree_path, sparse_paths, app.clone())?;
+            watchers.insert(task_id, watcher);
+        }
+        drop(watchers);
+        emit_diff_changed(app, task_id);
+        Ok(summary)
+    }
+
     pub fn stop_diff_watch(&self, req: StopDiffWatchRequest) -> Result<()> {
         self.remove_diff_watch(req.task_id);
         Ok(())
     }
 
+    pub fn get_transcript(&self, req: TranscriptGetRequest) -> Result<Transcript> {
+        let (worktree_path, agent_kind) = {
+            let tasks = self.inner.tasks.read();
+            let record = tasks.get(&req.task_id).ok_or(TaskError::NotFound)?;
+            (
+                PathBuf::from(&record.summary.worktree_path),
+                record.agent_kind.clone(),
+            )
+        };
+        Ok(load_transcript(
+            req.task_id,
+            &agent_kind,
+            &worktree_path,
+            req.session_id.as_deref(),
+        )?)
+    }
+
+    pub fn start_transcript_watch(
+        &self,
+        req: StartTranscriptWatchRequest,
+        app: &AppHandle,
+    ) -> Result<()> {
+        let task_id = req.task_id;
+        let (worktree_path, agent_kind) = {
+            let tasks = self.inner.tasks.read();
+            let record = tasks.get(&task_id).ok_or(TaskError::NotFound)?;
+            (
+                PathBuf::from(&record.summary.worktree_path),
+                record.agent_kind.clone(),
+            )
+        };
+        let mut watchers = self.inner.transcript_watchers.lock();
+        if watchers.contains_key(&task_id) {
+            return Ok(());
+        }
+        let watcher = TranscriptWatcher::new(task_id, &agent_kind, &worktree_path, app.clone())?;
+        watchers.insert(task_id, watcher);
+        Ok(())
+    }
+
+    pub fn stop_transcript_watch(&self, req: StopTranscriptWatchRequest) -> Result<()> {
+        self.inner.transcript_watchers.lock().remove(&req.task_id);
+        Ok(())
+    }
+
     pub fn commit_task(&self, req: CommitTaskRequest) -> Result<()> {
         let task_id = req.task_id;
         let message = req.message.trim();
@@ -634,19 +1130,175 @@ impl TaskManager {
         git_push(worktree_path.as_path(), remote.as_str(), branch.as_str(), set_upstream)
     }
 
-    fn apply_agent_status(&self, record: &mut TaskRecord, status: TaskStatus, app: &AppHandle) {
+    fn apply_agent_status(
+        &self,
+        tasks: &mut HashMap<Uuid, TaskRecord>,
+        task_id: Uuid,
+        status: TaskStatus,
+        app: &AppHandle,
+    ) {
+        let Some(record) = tasks.get_mut(&task_id) else {
+            return;
+        };
+        // A stopping agent still reacts to the interrupt; it must not undo the stop.
+        if record.summary.status == TaskStatus::Stopped {
+            return;
+        }
         if record.summary.status != status {
             record.summary.status = status;
-            emit_status(app, &record.summary);
+            if let Some(clock) = record.budget_clock.as_mut() {
+                clock.status_changed(Instant::now());
+            }
+            self.publish_task(tasks, task_id, app);
         }
     }
 
     pub fn handle_agent_status(&self, task_id: Uuid, status: TaskStatus, app: &AppHandle) {
         debug!("agent_status task_id={} status={:?}", task_id, status);
         let mut tasks = self.inner.tasks.write();
-        if let Some(record) = tasks.get_mut(&task_id) {
-            self.apply_agent_status(record, status, app);
+        self.apply_agent_status(&mut tasks, task_id, status, app);
+        if status != TaskStatus::AwaitingApproval {
+            return;
         }
+        let manager = self.clone();
+        let app = app.clone();
+        std::thread::spawn(move || manager.review_approval(task_id, &app));
+    }
+
+    /// Stops the agent once it runs past one of its budgets. The stop waits out a grace
+    /// period, so it runs on a thread of its own rather than the PTY supervisor's.
+    pub fn handle_agent_tick(&self, task_id: Uuid, now: Instant, app: &AppHandle) {
+        let exceeded = {
+            let mut tasks = self.inner.tasks.write();
+            let Some(record) = tasks.get_mut(&task_id) else {
+                return;
+            };
+            let status = record.summary.status;
+            if status == TaskStatus::Stopped {
+                return;
+            }
+            let last_output = record.agent.as_ref().and_then(|agent| agent.last_output());
+            record
+                .budget_clock
+                .as_mut()
+                .and_then(|clock| clock.check(status, last_output, now))
+        };
+        let Some((budget, limit_secs)) = exceeded else {
+            return;
+        };
+        warn!(
+            "budget exceeded task_id={} budget={} limit_secs={}",
+            task_id,
+            budget.as_str(),
+            limit_secs
+        );
+        emit_budget_exceeded(app, task_id, budget, limit_secs);
+        self.record_history(
+            task_id,
+            TaskHistoryEvent::BudgetExceeded { budget, limit_secs },
+            app,
+        );
+        let manager = self.clone();
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let reason = StopReason::BudgetExceeded { budget, limit_secs };
+            if let Err(err) = manager.stop_agent(task_id, None, reason, &app) {
+                warn!("failed to stop agent task_id={} err={}", task_id, err);
+            }
+        });
+    }
+
+    /// Answers the prompt when a repo approval rule matches its command, and otherwise
+    /// hands it to the user.
+    fn review_approval(&self, task_id: Uuid, app: &AppHandle) {
+        let base_repo_path = {
+            let tasks = self.inner.tasks.read();
+            let Some(record) = tasks.get(&task_id) else {
+                return;
+            };
+            PathBuf::from(&record.summary.base_repo_path)
+        };
+        let policy = match ApprovalPolicy::load(&base_repo_path) {
+            Ok(policy) => Some(policy).filter(|policy| !policy.is_empty()),
+            Err(err) => {
+                warn!("failed to load approval rules task_id={} err={}", task_id, err);
+                None
+            }
+        };
+        if policy.is_some() {
+            // Let the dialog finish drawing so the whole command is on screen.
+            std::thread::sleep(APPROVAL_SETTLE_DELAY);
+        }
+        loop {
+            let (command, agent_kind, worktree_path) = {
+                let tasks = self.inner.tasks.read();
+                let Some(record) = tasks.get(&task_id) else {
+                    return;
+                };
+                if record.summary.status != TaskStatus::AwaitingApproval {
+                    return;
+                }
+                (
+                    record
+                        .agent
+                        .as_ref()
+                        .and_then(|agent| agent.pending_approval()),
+                    record.agent_kind.clone(),
+                    PathBuf::from(&record.summary.worktree_path),
+                )
+            };
+            let verdict = policy
+                .as_ref()
+                .zip(command.as_deref())
+                .and_then(|(policy, command)| policy.evaluate(command, &worktree_path));
+            if let (Some(verdict), Some(command)) = (verdict, &command) {
+                info!(
+                    "auto_approval task_id={} decision={:?} rule={} command={}",
+                    task_id, verdict.decision, verdict.rule, command
+                );
+                match self.answer_approval(task_id, verdict.decision, Some(command)) {
+                    Ok(true) => {
+                        self.record_history(
+                            task_id,
+                            TaskHistoryEvent::AutoApproval {
+                                command: command.clone(),
+                                decision: verdict.decision,
+                                rule: verdict.rule,
+                            },
+                            app,
+                        );
+                        return;
+                    }
+                    Ok(false) => {
+                        debug!("approval prompt changed before answering task_id={}", task_id);
+                        std::thread::sleep(APPROVAL_SETTLE_DELAY);
+                        continue;
+                    }
+                    Err(err) => {
+                        warn!("failed to answer approval task_id={} err={}", task_id, err);
+                    }
+                }
+            }
+            emit_approval_requested(app, task_id, agent_kind, command);
+            return;
+        }
+    }
+
+    fn record_history(&self, task_id: Uuid, event: TaskHistoryEvent, app: &AppHandle) {
+        let mut tasks = self.inner.tasks.write();
+        let Some(record) = tasks.get_mut(&task_id) else {
+            return;
+        };
+        let history = &mut record.summary.history;
+        history.push(TaskHistoryEntry {
+            at: Utc::now(),
+            event,
+        });
+        if history.len() > MAX_HISTORY_ENTRIES {
+            let excess = history.len() - MAX_HISTORY_ENTRIES;
+            history.drain(..excess);
+        }
+        self.publish_task(&tasks, task_id, app);
     }
 
     pub fn handle_agent_output(&self, task_id: Uuid, chunk: String, app: &AppHandle) {
@@ -658,6 +1310,112 @@ impl TaskManager {
         info!("agent_exit task_id={} exit_code={}", task_id, exit_code);
         let _ = self.finish_task(task_id, exit_code, app);
         emit_terminal_exit(app, task_id, exit_code, TerminalKind::Agent);
+        self.schedule_restart(task_id, exit_code, app);
+    }
+
+    /// Starts a failed agent again as its restart policy allows. The backoff is waited
+    /// out on a thread of its own, off the PTY supervisor's.
+    fn schedule_restart(&self, task_id: Uuid, exit_code: i32, app: &AppHandle) {
+        let (request, attempts, max_retries, backoff_ms, failure, ended_at) = {
+            let tasks = self.inner.tasks.read();
+            let Some(record) = tasks.get(&task_id) else {
+                return;
+            };
+            if record.summary.status != TaskStatus::Failed {
+                return;
+            }
+            let Some(plan) = &record.restart else {
+                return;
+            };
+            let RestartPolicy::OnFailure {
+                max_retries,
+                backoff_ms,
+            } = plan.policy
+            else {
+                return;
+            };
+            let failure = record
+                .summary
+                .failure_reason
+                .as_ref()
+                .map_or(AgentFailure::Crashed, |reason| reason.failure);
+            (
+                plan.request.clone(),
+                record.summary.restart_attempts,
+                max_retries,
+                backoff_ms,
+                failure,
+                record.summary.ended_at,
+            )
+        };
+        if !failure.recoverable() || attempts >= max_retries {
+            info!(
+                "restart abandoned task_id={} failure={:?} attempts={}",
+                task_id, failure, attempts
+            );
+            self.record_history(
+                task_id,
+                TaskHistoryEvent::RestartAbandoned { failure, attempts },
+                app,
+            );
+            return;
+        }
+
+        let attempt = attempts + 1;
+        let delay = Duration::from_millis(backoff_ms.saturating_mul(1 << attempts.min(16)))
+            .min(MAX_RESTART_DELAY);
+        info!(
+            "restarting agent task_id={} attempt={} delay_ms={}",
+            task_id,
+            attempt,
+            delay.as_millis()
+        );
+        self.record_history(
+            task_id,
+            TaskHistoryEvent::AgentRestart {
+                attempt,
+                max_retries,
+                exit_code,
+                delay_ms: delay.as_millis() as u64,
+            },
+            app,
+        );
+        let manager = self.clone();
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            // Leave the task alone if it was started or discarded in the meantime.
+            let unchanged = manager
+                .inner
+                .tasks
+                .read()
+                .get(&task_id)
+                .is_some_and(|record| {
+                    record.summary.status == TaskStatus::Failed
+                        && record.summary.ended_at == ended_at
+                });
+            if !unchanged {
+                return;
+            }
+            if let Err(err) = manager.start_agent(request, attempt, &app) {
+                warn!("failed to restart agent task_id={} err={}", task_id, err);
+                let failure = manager
+                    .inner
+                    .tasks
+                    .read()
+                    .get(&task_id)
+                    .and_then(|record| record.summary.failure_reason.as_ref())
+                    .map_or(AgentFailure::Crashed, |reason| reason.failure);
+                manager.record_history(
+                    task_id,
+                    TaskHistoryEvent::RestartAbandoned {
+                        failure,
+                        attempts: attempt,
+                    },
+                    &app,
+                );
+            }
+        });
     }
 
     fn handle_worktree_terminal_exit(&self, task_id: Uuid, exit_code: i32, app: &AppHandle) {
@@ -679,74 +1437,28 @@ impl TaskManager {
         rows: u16,
         cols: u16,
         app: &AppHandle,
-    ) -> Result<TaskRuntime> {
-        let pty_system = native_pty_system();
-        let pair = pty_system.openpty(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })?;
-
-        let master = pair.master;
-        let writer = master
-            .take_writer()
-            .context("failed to obtain worktree terminal writer")?;
-        let reader = master
-            .try_clone_reader()
-            .context("failed to clone worktree terminal reader")?;
-        let master = Arc::new(Mutex::new(master));
-        let writer = Arc::new(Mutex::new(writer));
-
+    ) -> Result<PtyProcess> {
         let command = build_worktree_shell_command(worktree_path);
-        let child = pair
-            .slave
-            .spawn_command(command)
-            .context("failed to start worktree terminal")?;
-        let child: Arc<Mutex<ChildHandle>> = Arc::new(Mutex::new(child));
-
         let output_app = app.clone();
-        std::thread::spawn(move || {
-            let mut reader = reader;
-            let mut buffer = [0u8; 8192];
-            loop {
-                match reader.read(&mut buffer) {
-                    Ok(0) => break,
-                    Ok(size) => {
-                        let chunk = String::from_utf8_lossy(&buffer[..size]).to_string();
-                        emit_terminal_output(&output_app, task_id, chunk, TerminalKind::Worktree);
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
-
         let exit_manager = self.clone();
         let exit_app = app.clone();
-        let exit_child = child.clone();
-        std::thread::spawn(move || {
-            let exit_code = loop {
-                {
-                    let mut child_guard = exit_child.lock();
-                    match child_guard.try_wait() {
-                        Ok(Some(status)) => {
-                            let code = status.exit_code() as i32;
-                            break if status.success() { 0 } else { code };
-                        }
-                        Ok(None) => {}
-                        Err(_) => break 1,
-                    }
-                }
-                std::thread::sleep(std::time::Duration::from_millis(200));
-            };
-            exit_manager.handle_worktree_terminal_exit(task_id, exit_code, &exit_app);
-        });
-
-        Ok(TaskRuntime {
-            child,
-            writer,
-            master,
-        })
+        let process = PtyProcess::spawn(
+            command,
+            "worktree terminal",
+            rows,
+            cols,
+            PtyHandlers {
+                on_output: Box::new(move |raw: &[u8]| {
+                    let chunk = String::from_utf8_lossy(raw).to_string();
+                    emit_terminal_output(&output_app, task_id, chunk, TerminalKind::Worktree);
+                }),
+                on_tick: None,
+                on_exit: Box::new(move |exit_code: i32| {
+                    exit_manager.handle_worktree_terminal_exit(task_id, exit_code, &exit_app);
+                }),
+            },
+        )?;
+        Ok(process)
     }
 
     fn contains_worktree_path(&self, path: &Path) -> bool {
@@ -770,12 +1482,32 @@ impl TaskManager {
         let repo_root = get_repo_root(&provided_path)?
             .canonicalize()
             .unwrap_or_else(|_| provided_path.clone());
-        let managed_root = managed_worktree_root(&repo_root)?;
-        let base_repo_head = run_git(&repo_root, ["rev-parse", "HEAD"])?;
-        let base_repo_branch =
-            run_git(&repo_root, ["rev-parse", "--abbrev-ref", "HEAD"]).unwrap_or_else(|_| {
-                "HEAD".to_string()
-            });
+        let default_root = default_worktree_root(&repo_root);
+        let worktree_roots = [
+            managed_worktree_root(&repo_root)?,
+            default_root.canonicalize().unwrap_or(default_root),
+        ];
+        let repo_path_display = normalize_path_string(&repo_root);
+        self.inner.loaded_repos.lock().insert(repo_path_display.clone());
+        let mut stored_by_path: HashMap<String, StoredTask> = load_tasks(&repo_root)
+            .unwrap_or_else(|err| {
+                warn!(
+                    "failed to load stored tasks base_repo_path={} err={}",
+                    repo_path_display, err
+                );
+                Vec::new()
+            })
+            .into_iter()
+            .map(|stored| (repo_key(Path::new(&stored.summary.worktree_path)), stored))
+            .collect();
+        let base_repo_head = run_git(&repo_root, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        let base_repo_branch = default_branch(&repo_root).unwrap_or_else(|| {
+            run_git(&repo_root, ["rev-parse", "--abbrev-ref", "HEAD"])
+                .map(|output| output.trim().to_string())
+                .unwrap_or_else(|_| "HEAD".to_string())
+        });
         let entries = list_worktrees(&repo_root)?;
         let mut inserted = Vec::new();
         for entry in entries {
@@ -786,13 +1518,44 @@ impl TaskManager {
             if canonical_path == repo_root {
                 continue;
             }
-            if !canonical_path.starts_with(&managed_root) {
+            let worktree_path_display = normalize_path_string(&canonical_path);
+            // Worktrees made before the root was reconfigured are still found via the store.
+            let is_managed = worktree_roots.iter().any(|root| canonical_path.starts_with(root))
+                || stored_by_path.contains_key(&worktree_path_display);
+            if !is_managed {
                 continue;
             }
             if self.contains_worktree_path(&canonical_path) {
                 continue;
             }
-            let worktree_path_display = normalize_path_string(&canonical_path);
+            if let Some(stored) = stored_by_path.remove(&worktree_path_display) {
+                let mut summary =
+                    restore_summary(stored.summary, worktree_path_display, &repo_path_display);
+                if let Some(fork_point) =
+                    resolve_fork_point(&canonical_path, &summary.base_branch)
+                {
+                    summary.base_commit = fork_point;
+                }
+                if self.inner.tasks.read().contains_key(&summary.task_id) {
+                    continue;
+                }
+                self.inner.tasks.write().insert(
+                    summary.task_id,
+                    TaskRecord {
+                        agent: None,
+                        agent_kind: stored.agent_kind,
+                        summary: summary.clone(),
+                        runtime: None,
+                        budget_clock: None,
+                        restart: None,
+                        shell: None,
+                        creation: None,
+                    },
+                );
+                emit_status(app, &summary);
+                inserted.push(summary);
+                continue;
+            }
             let branch_name = entry
                 .branch
                 .as_ref()
@@ -801,6 +1564,8 @@ impl TaskManager {
                     let short_head: String = entry.head.chars().take(7).collect();
                     format!("detached-{}", short_head)
                 });
+            let base_commit = resolve_fork_point(&canonical_path, &base_repo_branch)
+                .unwrap_or_else(|| base_repo_head.clone());
             let summary = TaskSummary {
                 task_id: Uuid::new_v4(),
                 title: format_title_from_branch(&branch_name),
@@ -810,24 +1575,44 @@ impl TaskManager {
                 ended_at: None,
                 worktree_path: worktree_path_display,
                 branch_name,
+                branch_mode: BranchMode::New,
                 base_branch: base_repo_branch.clone(),
-                base_repo_path: normalize_path_string(&repo_root),
-                base_commit: base_repo_head.clone(),
+                base_repo_path: repo_path_display.clone(),
+                base_commit,
+                sparse_paths: Vec::new(),
+                prompt: None,
                 exit_code: None,
+                failure_reason: None,
+                launch_options: None,
+                budgets: None,
+                stop_reason: None,
+                restart_attempts: 0,
+                history: Vec::new(),
             };
             self.inner.tasks.write().insert(
                 summary.task_id,
                 TaskRecord {
-                    agent: build_agent(AgentKind::Codex),
+                    agent: None,
                     agent_kind: AgentKind::Codex,
                     summary: summary.clone(),
                     runtime: None,
+                    budget_clock: None,
+                    restart: None,
                     shell: None,
+                    creation: None,
                 },
             );
             emit_status(app, &summary);
             inserted.push(summary);
         }
+        if !stored_by_path.is_empty() {
+            debug!(
+                "dropping {} stored tasks without a worktree base_repo_path={}",
+                stored_by_path.len(),
+                repo_path_display
+            );
+        }
+        self.persist_tasks(&self.inner.tasks.read(), &repo_path_display);
         Ok(inserted)
     }
 
@@ -865,25 +1650,101 @@ impl TaskManager {
         record.summary.exit_code = Some(exit_code);
         record.summary.ended_at = Some(Utc::now());
         record.runtime = None;
+        record.budget_clock = None;
         let target_status = match record.summary.status {
             TaskStatus::Stopped => TaskStatus::Stopped,
             TaskStatus::Discarded => TaskStatus::Discarded,
             _ if exit_code == 0 => TaskStatus::Completed,
             _ => TaskStatus::Failed,
         };
+        if target_status == TaskStatus::Failed {
+            let failure = record
+                .agent
+                .as_ref()
+                .map_or(AgentFailure::Crashed, |agent| agent.classify_exit(exit_code));
+            record.summary.failure_reason = Some(failure_reason(failure, &record.agent_kind));
+        }
         record.summary.status = target_status;
-        emit_status(app, &record.summary);
+        self.publish_task(&tasks, task_id, app);
         Ok(())
     }
+
+    /// Emits the task's current summary and queues a write of its repository's task store.
+    fn publish_task(&self, tasks: &HashMap<Uuid, TaskRecord>, task_id: Uuid, app: &AppHandle) {
+        if let Some(record) = tasks.get(&task_id) {
+            emit_status(app, &record.summary);
+            self.persist_tasks(tasks, &record.summary.base_repo_path);
+        }
+    }
+
+    fn persist_tasks(&self, tasks: &HashMap<Uuid, TaskRecord>, base_repo_path: &str) {
+        let stored = tasks
+            .values()
+            .filter(|record| record.summary.base_repo_path == base_repo_path)
+            .map(|record| StoredTask {
+                summary: record.summary.clone(),
+                agent_kind: record.agent_kind.clone(),
+            })
+            .collect();
+        queue_save(PathBuf::from(base_repo_path), stored);
+    }
+
+    /// Restores the repository's stored tasks before anything can overwrite the store.
+    fn ensure_tasks_loaded(&self, repo_root: &Path, app: &AppHandle) -> Result<()> {
+        let key = repo_key(repo_root);
+        if self.inner.loaded_repos.lock().contains(&key) {
+            return Ok(());
+        }
+        self.register_existing_worktrees(key, app).map(|_| ())
+    }
 }
 
 use anyhow::Context;
 
+/// Commit where the worktree's history left `base_branch`, so branch diffs only show task work.
+pub(crate) fn resolve_fork_point(worktree_path: &Path, base_branch: &str) -> Option<String> {
+    merge_base(worktree_path, "HEAD", base_branch)
+        .map_err(|err| {
+            debug!(
+                "no fork point worktree={} base_branch={} err={}",
+                worktree_path.display(),
+                base_branch,
+                err.to_string().trim()
+            );
+        })
+        .ok()
+        .filter(|commit| !commit.is_empty())
+}
+
+/// Brings a stored summary back into a freshly started app, where no agent can still be running.
+fn restore_summary(
+    mut summary: TaskSummary,
+    worktree_path: String,
+    base_repo_path: &str,
+) -> TaskSummary {
+    summary.worktree_path = worktree_path;
+    summary.base_repo_path = base_repo_path.to_string();
+    summary.status = match summary.status {
+        TaskStatus::Completed => TaskStatus::Completed,
+        TaskStatus::Failed | TaskStatus::CreatingWorktree | TaskStatus::SettingUp => {
+            TaskStatus::Failed
+        }
+        _ => TaskStatus::Stopped,
+    };
+    summary
+}
+
 impl DiffWatcher {
-    fn new(task_id: Uuid, path: PathBuf, app: AppHandle) -> Result<Self> {
+    fn new(
+        task_id: Uuid,
+        path: PathBuf,
+        sparse_paths: Vec<String>,
+        app: AppHandle,
+    ) -> Result<Self> {
+        let root = path.clone();
         let mut watcher = notify::recommended_watcher(move |res| match res {
             Ok(event) => {
-                if should_emit_diff_event(&event) {
+                if should_emit_diff_event(&event, &root, &sparse_paths) {
                     emit_diff_changed(&app, task_id);
                 }
             }
@@ -899,9 +1760,24 @@ impl DiffWatcher {
     }
 }
 
-fn should_emit_diff_event(event: &Event) -> bool {
-    matches!(
+fn should_emit_diff_event(event: &Event, root: &Path, sparse_paths: &[String]) -> bool {
+    if !matches!(
         event.kind,
         EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
-    )
+    ) {
+        return false;
+    }
+    if sparse_paths.is_empty() {
+        return true;
+    }
+    // Cone mode always includes root-level files.
+    event.paths.iter().any(|path| {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return true;
+        };
+        relative.components().count() <= 1
+            || sparse_paths
+                .iter()
+                .any(|sparse| relative.starts_with(Path::new(sparse)))
+    })
 }
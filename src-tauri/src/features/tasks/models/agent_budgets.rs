@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Limits after which illuc stops a task's agent. Unset limits never run out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentBudgets {
+    /// Wall-clock time since the agent started.
+    pub max_runtime_secs: Option<u64>,
+    /// Time spent `Working` without printing anything, e.g. on a hung test.
+    pub max_working_secs: Option<u64>,
+    /// Time an approval prompt may go unanswered.
+    pub max_awaiting_approval_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Budget {
+    Runtime,
+    Working,
+    AwaitingApproval,
+}
+
+impl AgentBudgets {
+    /// Limits set here win over `defaults`.
+    pub fn with_defaults(self, defaults: &AgentBudgets) -> Self {
+        Self {
+            max_runtime_secs: self.max_runtime_secs.or(defaults.max_runtime_secs),
+            max_working_secs: self.max_working_secs.or(defaults.max_working_secs),
+            max_awaiting_approval_secs: self
+                .max_awaiting_approval_secs
+                .or(defaults.max_awaiting_approval_secs),
+        }
+    }
+
+    pub fn limit_secs(&self, budget: Budget) -> Option<u64> {
+        match budget {
+            Budget::Runtime => self.max_runtime_secs,
+            Budget::Working => self.max_working_secs,
+            Budget::AwaitingApproval => self.max_awaiting_approval_secs,
+        }
+    }
+}
+
+impl Budget {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Budget::Runtime => "runtime",
+            Budget::Working => "working",
+            Budget::AwaitingApproval => "awaitingApproval",
+        }
+    }
+}
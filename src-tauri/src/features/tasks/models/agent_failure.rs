@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Why an agent failed to start or exited with an error.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AgentFailure {
+    /// The agent's command is not installed or not on `PATH`.
+    BinaryNotFound,
+    /// The agent needs its user to log in first.
+    NotAuthenticated,
+    /// The provider refused requests for hitting a rate limit or running out of quota.
+    RateLimited,
+    /// The provider's API could not be reached.
+    NetworkUnreachable,
+    Crashed,
+}
+
+impl AgentFailure {
+    /// Whether starting the agent again can help without the user stepping in.
+    pub fn recoverable(self) -> bool {
+        !matches!(self, AgentFailure::BinaryNotFound | AgentFailure::NotAuthenticated)
+    }
+}
+
+/// A classified failure, with what the user can do about it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureReason {
+    pub failure: AgentFailure,
+    pub hint: String,
+}
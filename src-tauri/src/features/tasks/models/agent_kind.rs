@@ -1,8 +1,44 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Deserialize, Clone, Copy)]
-#[serde(rename_all = "lowercase")]
+/// Built-in agents plus agents defined in config, which are referred to by id.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AgentKind {
     Codex,
     Copilot,
+    Aider,
+    Custom(String),
+}
+
+impl AgentKind {
+    pub fn id(&self) -> &str {
+        match self {
+            AgentKind::Codex => "codex",
+            AgentKind::Copilot => "copilot",
+            AgentKind::Aider => "aider",
+            AgentKind::Custom(id) => id,
+        }
+    }
+
+    /// Built-in ids take precedence over configured agents with the same id.
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "codex" => AgentKind::Codex,
+            "copilot" => AgentKind::Copilot,
+            "aider" => AgentKind::Aider,
+            _ => AgentKind::Custom(id.to_string()),
+        }
+    }
+}
+
+impl Serialize for AgentKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for AgentKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Ok(AgentKind::from_id(&id))
+    }
 }
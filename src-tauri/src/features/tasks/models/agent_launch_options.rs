@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// Flags an agent is launched with, on top of what illuc always passes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentLaunchOptions {
+    pub model: Option<String>,
+    pub effort: Option<ReasoningEffort>,
+    /// Codex config profile, passed as `-p`.
+    pub profile: Option<String>,
+    pub sandbox: Option<SandboxMode>,
+    /// Passed to the agent unchanged, after everything else.
+    pub extra_args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+/// How freely the agent may act without asking.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SandboxMode {
+    /// Reads anything; asks before editing or running commands.
+    ReadOnly,
+    /// Edits the worktree; asks before running commands.
+    WorkspaceWrite,
+    /// Edits and runs commands in the worktree without asking.
+    FullAuto,
+}
+
+impl AgentLaunchOptions {
+    /// Fields set here win over `defaults`; extra args follow the defaults' ones.
+    pub fn with_defaults(self, defaults: &AgentLaunchOptions) -> Self {
+        let mut extra_args = defaults.extra_args.clone();
+        extra_args.extend(self.extra_args);
+        Self {
+            model: self.model.or_else(|| defaults.model.clone()),
+            effort: self.effort.or(defaults.effort),
+            profile: self.profile.or_else(|| defaults.profile.clone()),
+            sandbox: self.sandbox.or(defaults.sandbox),
+            extra_args,
+        }
+    }
+}
+
+impl ReasoningEffort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReasoningEffort::Minimal => "minimal",
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// An answer to an agent's request to run a command or tool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ApprovalDecision {
+    Approve,
+    /// Approve and stop asking about the same kind of request for this session.
+    ApproveAlways,
+    Deny,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BranchMode {
+    /// Create `branch_name` from the base ref; the task owns the branch.
+    #[default]
+    New,
+    /// Check out a branch that already exists locally or on a remote.
+    Existing,
+}
@@ -1,13 +1,33 @@
+pub mod agent_budgets;
+pub mod agent_failure;
 pub mod agent_kind;
+pub mod agent_launch_options;
+pub mod agent_session;
+pub mod approval_decision;
+pub mod branch_mode;
 pub mod base_repo_info;
 pub mod diff_payload;
+pub mod restart_policy;
+pub mod stop_outcome;
+pub mod stop_reason;
 pub mod terminal_kind;
+pub mod task_history;
 pub mod task_status;
 pub mod task_summary;
 
+pub use agent_budgets::{AgentBudgets, Budget};
+pub use agent_failure::{AgentFailure, FailureReason};
 pub use agent_kind::AgentKind;
+pub use agent_launch_options::{AgentLaunchOptions, SandboxMode};
+pub use agent_session::AgentSession;
+pub use approval_decision::ApprovalDecision;
+pub use branch_mode::BranchMode;
 pub use base_repo_info::BaseRepoInfo;
 pub use diff_payload::DiffPayload;
+pub use restart_policy::RestartPolicy;
+pub use stop_outcome::StopOutcome;
+pub use stop_reason::StopReason;
 pub use terminal_kind::TerminalKind;
+pub use task_history::{TaskHistoryEntry, TaskHistoryEvent};
 pub use task_status::TaskStatus;
 pub use task_summary::TaskSummary;
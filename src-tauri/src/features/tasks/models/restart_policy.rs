@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether illuc starts an agent again after it exits with an error.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    /// Resumes the agent's session up to `max_retries` times, waiting `backoff_ms`
+    /// before the first restart and twice as long before each one after it.
+    OnFailure {
+        max_retries: u32,
+        #[serde(default = "default_backoff_ms")]
+        backoff_ms: u64,
+    },
+}
+
+fn default_backoff_ms() -> u64 {
+    5000
+}
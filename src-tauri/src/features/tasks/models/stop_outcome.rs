@@ -0,0 +1,15 @@
+use crate::features::tasks::models::task_summary::TaskSummary;
+use serde::Serialize;
+
+/// How a task's agent went down when it was stopped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopOutcome {
+    pub task: TaskSummary,
+    /// The agent and its child processes exited on the interrupt, within the grace
+    /// period. Where process groups cannot be observed, only the agent is known to have.
+    pub graceful: bool,
+    /// No process of the agent's process group is left, whether or not it had to be
+    /// killed. `None` where process groups cannot be observed, as on Windows.
+    pub exited: Option<bool>,
+}
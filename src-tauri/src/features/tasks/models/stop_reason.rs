@@ -0,0 +1,12 @@
+use crate::features::tasks::models::agent_budgets::Budget;
+use serde::{Deserialize, Serialize};
+
+/// Why a task's agent was last stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum StopReason {
+    /// Stopped through `task_stop`.
+    User,
+    /// Ran past one of its budgets.
+    BudgetExceeded { budget: Budget, limit_secs: u64 },
+}
@@ -0,0 +1,40 @@
+use crate::features::tasks::models::agent_budgets::Budget;
+use crate::features::tasks::models::agent_failure::AgentFailure;
+use crate::features::tasks::models::agent_kind::AgentKind;
+use crate::features::tasks::models::approval_decision::ApprovalDecision;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Something illuc did to a task on its own, kept so it can be reviewed later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskHistoryEntry {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: TaskHistoryEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TaskHistoryEvent {
+    /// An approval prompt answered by a rule from the repo config.
+    AutoApproval {
+        command: String,
+        decision: ApprovalDecision,
+        rule: String,
+    },
+    /// Forbidden commands the agent started without any way to refuse.
+    UnenforcedRules { agent: AgentKind, rules: Vec<String> },
+    /// The agent ran past a budget and was stopped.
+    BudgetExceeded { budget: Budget, limit_secs: u64 },
+    /// The agent exited with an error and is started again after `delay_ms`.
+    AgentRestart {
+        attempt: u32,
+        max_retries: u32,
+        exit_code: i32,
+        delay_ms: u64,
+    },
+    /// No further restarts, because of a failure restarting cannot fix or because
+    /// the retries ran out.
+    RestartAbandoned { failure: AgentFailure, attempts: u32 },
+}
@@ -1,9 +1,15 @@
+use crate::features::tasks::models::agent_budgets::AgentBudgets;
+use crate::features::tasks::models::agent_failure::FailureReason;
+use crate::features::tasks::models::agent_launch_options::AgentLaunchOptions;
+use crate::features::tasks::models::branch_mode::BranchMode;
+use crate::features::tasks::models::stop_reason::StopReason;
+use crate::features::tasks::models::task_history::TaskHistoryEntry;
 use crate::features::tasks::models::task_status::TaskStatus;
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskSummary {
     pub task_id: Uuid,
@@ -14,8 +20,34 @@ pub struct TaskSummary {
     pub ended_at: Option<DateTime<Utc>>,
     pub worktree_path: String,
     pub branch_name: String,
+    #[serde(default)]
+    pub branch_mode: BranchMode,
     pub base_branch: String,
     pub base_repo_path: String,
     pub base_commit: String,
+    /// Cone-mode sparse-checkout directories; empty for a full checkout.
+    #[serde(default)]
+    pub sparse_paths: Vec<String>,
+    /// Instruction the task was created with, used for the agent's first run.
+    #[serde(default)]
+    pub prompt: Option<String>,
     pub exit_code: Option<i32>,
+    /// Why the agent last failed to start or exited with an error.
+    #[serde(default)]
+    pub failure_reason: Option<FailureReason>,
+    /// Launch options of the agent's latest start.
+    #[serde(default)]
+    pub launch_options: Option<AgentLaunchOptions>,
+    /// Budgets of the agent's latest start.
+    #[serde(default)]
+    pub budgets: Option<AgentBudgets>,
+    /// Set while the task is `Stopped` by illuc or the user.
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
+    /// Restarts after failures since the agent was last started by hand.
+    #[serde(default)]
+    pub restart_attempts: u32,
+    /// Decisions illuc made on the task's behalf, oldest first.
+    #[serde(default)]
+    pub history: Vec<TaskHistoryEntry>,
 }
@@ -5,4 +5,6 @@ use serde::{Deserialize, Serialize};
 pub enum TerminalKind {
     Agent,
     Worktree,
+    /// Read-only output of the worktree setup script.
+    Setup,
 }
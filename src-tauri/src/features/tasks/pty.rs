@@ -0,0 +1,183 @@
+use anyhow::Context;
+use parking_lot::Mutex;
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use std::io::{Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How often running processes are checked for exit and get `on_tick`, which drives
+/// idle detection. A reader reaching end of file triggers a check right away.
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+pub type ChildHandle = Box<dyn Child + Send + Sync>;
+
+pub type WriteHandle = Arc<Mutex<Box<dyn Write + Send>>>;
+
+pub type MasterHandle = Arc<Mutex<Box<dyn MasterPty + Send>>>;
+
+type OutputHandler = Box<dyn FnMut(&[u8]) + Send>;
+
+type TickHandler = Box<dyn FnMut(Instant) + Send>;
+
+type ExitHandler = Box<dyn FnOnce(i32) + Send>;
+
+/// A process running on its own PTY: an agent or a worktree terminal.
+pub struct PtyProcess {
+    pub child: Arc<Mutex<ChildHandle>>,
+    pub writer: WriteHandle,
+    pub master: MasterHandle,
+}
+
+pub struct PtyHandlers {
+    /// Raw output, called from the process's reader thread.
+    pub on_output: OutputHandler,
+    /// Called by the supervisor every 250 ms while the process runs.
+    pub on_tick: Option<TickHandler>,
+    /// Called by the supervisor once the process has exited.
+    pub on_exit: ExitHandler,
+}
+
+impl PtyProcess {
+    /// Starts `command` on a new PTY. Output is read on a thread of its own; exits and
+    /// ticks come from the supervisor thread shared by all processes.
+    pub fn spawn(
+        command: CommandBuilder,
+        label: &str,
+        rows: u16,
+        cols: u16,
+        handlers: PtyHandlers,
+    ) -> anyhow::Result<Self> {
+        let pair = native_pty_system().openpty(PtySize {
+            rows: rows.max(1),
+            cols: cols.max(1),
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+
+        let master = pair.master;
+        let writer = master
+            .take_writer()
+            .context("failed to obtain pty writer")?;
+        let mut reader = master
+            .try_clone_reader()
+            .context("failed to clone pty reader")?;
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .with_context(|| format!("failed to start {}", label))?;
+        let child: Arc<Mutex<ChildHandle>> = Arc::new(Mutex::new(child));
+
+        let PtyHandlers {
+            mut on_output,
+            on_tick,
+            on_exit,
+        } = handlers;
+        let _ = supervisor().send(SupervisorMessage::Watch(Supervised {
+            child: child.clone(),
+            on_tick,
+            on_exit: Some(on_exit),
+        }));
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 8192];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(size) => on_output(&buffer[..size]),
+                }
+            }
+            let _ = supervisor().send(SupervisorMessage::Wake);
+        });
+
+        Ok(Self {
+            child,
+            writer: Arc::new(Mutex::new(writer)),
+            master: Arc::new(Mutex::new(master)),
+        })
+    }
+}
+
+enum SupervisorMessage {
+    Watch(Supervised),
+    /// A reader hit end of file, so its process has most likely exited.
+    Wake,
+}
+
+struct Supervised {
+    child: Arc<Mutex<ChildHandle>>,
+    on_tick: Option<TickHandler>,
+    on_exit: Option<ExitHandler>,
+}
+
+impl Supervised {
+    fn exit_code(&self) -> Option<i32> {
+        match self.child.lock().try_wait() {
+            Ok(Some(status)) if status.success() => Some(0),
+            Ok(Some(status)) => Some(status.exit_code() as i32),
+            Ok(None) => None,
+            Err(_) => Some(1),
+        }
+    }
+}
+
+/// The one thread that reaps every PTY child and drives their ticks. It sleeps while
+/// nothing runs.
+fn supervisor() -> &'static Sender<SupervisorMessage> {
+    static SUPERVISOR: OnceLock<Sender<SupervisorMessage>> = OnceLock::new();
+    SUPERVISOR.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || supervise(receiver));
+        sender
+    })
+}
+
+fn supervise(receiver: Receiver<SupervisorMessage>) {
+    let mut processes: Vec<Supervised> = Vec::new();
+    let mut next_tick = Instant::now();
+    loop {
+        let message = if processes.is_empty() {
+            match receiver.recv() {
+                Ok(message) => Some(message),
+                Err(_) => return,
+            }
+        } else {
+            let wait = next_tick.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(wait) {
+                Ok(message) => Some(message),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        };
+        let now = Instant::now();
+        let tick = now >= next_tick;
+        match message {
+            Some(SupervisorMessage::Watch(process)) => {
+                if processes.is_empty() {
+                    next_tick = now + TICK_INTERVAL;
+                }
+                processes.push(process);
+                continue;
+            }
+            Some(SupervisorMessage::Wake) => {}
+            None if tick => {}
+            None => continue,
+        }
+        processes.retain_mut(|process| match process.exit_code() {
+            Some(exit_code) => {
+                if let Some(on_exit) = process.on_exit.take() {
+                    on_exit(exit_code);
+                }
+                false
+            }
+            None => true,
+        });
+        if tick {
+            for process in &mut processes {
+                if let Some(on_tick) = process.on_tick.as_mut() {
+                    on_tick(now);
+                }
+            }
+            next_tick = now + TICK_INTERVAL;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::{Result, TaskError};
+use crate::features::tasks::config::{illuc_dir, RepoConfig};
+use crate::features::tasks::git::GitCancelHandle;
+use crate::features::tasks::pty::{PtyHandlers, PtyProcess};
+#[cfg(target_os = "windows")]
+use crate::utils::windows::{build_wsl_command, to_wsl_path};
+use portable_pty::CommandBuilder;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use uuid::Uuid;
+
+const SETUP_SCRIPT: &str = "setup.sh";
+const SETUP_ROWS: u16 = 40;
+const SETUP_COLS: u16 = 120;
+/// How often a running setup checks whether the task's creation was cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What to run in a fresh worktree before it is handed to an agent.
+pub enum SetupPlan {
+    Script(PathBuf),
+    Commands(Vec<String>),
+}
+
+/// `.illuc/setup.sh` wins over `setup.commands` in the repo config.
+pub fn setup_plan(repo_root: &Path, config: &RepoConfig) -> Option<SetupPlan> {
+    let script = illuc_dir(repo_root).join(SETUP_SCRIPT);
+    if script.is_file() {
+        return Some(SetupPlan::Script(script));
+    }
+    let commands: Vec<String> = config
+        .setup
+        .commands
+        .iter()
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .collect();
+    if commands.is_empty() {
+        None
+    } else {
+        Some(SetupPlan::Commands(commands))
+    }
+}
+
+fn build_setup_command(
+    plan: &SetupPlan,
+    task_id: Uuid,
+    repo_root: &Path,
+    worktree_path: &Path,
+) -> CommandBuilder {
+    // `-e` stops at the first failing command, `-x` echoes each one into the output.
+    // An executable script runs on its own, so its shebang picks the interpreter.
+    #[cfg(target_os = "windows")]
+    let mut command = match plan {
+        SetupPlan::Script(script) => {
+            let script = to_wsl_path(script).unwrap_or_else(|| script.to_string_lossy().to_string());
+            build_wsl_command(worktree_path, "bash", &["-ex", script.as_str()])
+        }
+        SetupPlan::Commands(commands) => {
+            let script = commands.join("\n");
+            build_wsl_command(worktree_path, "bash", &["-exc", script.as_str()])
+        }
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let mut command = {
+        let mut command = match plan {
+            SetupPlan::Script(script) if is_executable(script) => CommandBuilder::new(script),
+            SetupPlan::Script(script) => {
+                let mut command = CommandBuilder::new("sh");
+                command.arg("-ex");
+                command.arg(script);
+                command
+            }
+            SetupPlan::Commands(commands) => {
+                let mut command = CommandBuilder::new("sh");
+                command.arg("-exc");
+                command.arg(commands.join("\n"));
+                command
+            }
+        };
+        command.cwd(worktree_path);
+        command
+    };
+
+    command.env("ILLUC_TASK_ID", task_id.to_string());
+    command.env("ILLUC_BASE_REPO", repo_root);
+    command.env("ILLUC_WORKTREE", worktree_path);
+    command
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Runs the setup plan in a PTY, forwarding output as it arrives, and returns
+/// the exit code. Fails only if the process cannot be run or is cancelled.
+pub fn run_setup(
+    plan: &SetupPlan,
+    task_id: Uuid,
+    repo_root: &Path,
+    worktree_path: &Path,
+    cancel: &GitCancelHandle,
+    on_output: impl Fn(String) + Send + 'static,
+) -> Result<i32> {
+    let command = build_setup_command(plan, task_id, repo_root, worktree_path);
+    let (exit_sender, exit_receiver) = mpsc::channel();
+    let process = PtyProcess::spawn(
+        command,
+        "worktree setup",
+        SETUP_ROWS,
+        SETUP_COLS,
+        PtyHandlers {
+            on_output: Box::new(move |raw: &[u8]| {
+                on_output(String::from_utf8_lossy(raw).to_string());
+            }),
+            on_tick: None,
+            on_exit: Box::new(move |exit_code: i32| {
+                let _ = exit_sender.send(exit_code);
+            }),
+        },
+    )?;
+
+    let mut killed = false;
+    let exit_code = loop {
+        if cancel.is_cancelled() && !killed {
+            let _ = process.child.lock().kill();
+            killed = true;
+        }
+        match exit_receiver.recv_timeout(CANCEL_POLL_INTERVAL) {
+            Ok(exit_code) => break exit_code,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(TaskError::Message("worktree setup was not supervised".into()));
+            }
+        }
+    };
+    if cancel.is_cancelled() {
+        return Err(TaskError::Message("cancelled".into()));
+    }
+    Ok(exit_code)
+}
+
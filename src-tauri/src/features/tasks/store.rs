@@ -0,0 +1,98 @@
+use crate::error::Result;
+use crate::features::tasks::config::illuc_dir;
+use crate::features::tasks::{AgentKind, TaskSummary};
+use crate::utils::path::normalize_path_string;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::OnceLock;
+
+const TASK_STORE_FILE: &str = "tasks.json";
+const TASK_STORE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredTask {
+    pub summary: TaskSummary,
+    pub agent_kind: AgentKind,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskStoreFile {
+    version: u32,
+    tasks: Vec<StoredTask>,
+}
+
+pub fn task_store_path(repo_root: &Path) -> PathBuf {
+    illuc_dir(repo_root).join(TASK_STORE_FILE)
+}
+
+/// Normalized form of a repository path, used to group tasks per store file.
+pub fn repo_key(path: &Path) -> String {
+    normalize_path_string(&path.canonicalize().unwrap_or_else(|_| path.to_path_buf()))
+}
+
+pub fn load_tasks(repo_root: &Path) -> Result<Vec<StoredTask>> {
+    let path = task_store_path(repo_root);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read_to_string(&path)?;
+    let file: TaskStoreFile = serde_json::from_str(&data).map_err(anyhow::Error::from)?;
+    Ok(file.tasks)
+}
+
+/// Hands a snapshot of a repository's tasks to the store writer and returns at once, so
+/// callers holding the task lock never wait on disk.
+pub fn queue_save(repo_root: PathBuf, tasks: Vec<StoredTask>) {
+    let _ = store_writer().send((repo_root, tasks));
+}
+
+/// The one thread that writes task stores. Writing from a single thread keeps two saves
+/// from racing on the same temporary file.
+fn store_writer() -> &'static Sender<(PathBuf, Vec<StoredTask>)> {
+    static WRITER: OnceLock<Sender<(PathBuf, Vec<StoredTask>)>> = OnceLock::new();
+    WRITER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || write_stores(receiver));
+        sender
+    })
+}
+
+fn write_stores(receiver: Receiver<(PathBuf, Vec<StoredTask>)>) {
+    while let Ok((repo_root, tasks)) = receiver.recv() {
+        // Snapshots queued while the last write ran are superseded by the newest one.
+        let mut latest = HashMap::from([(repo_root, tasks)]);
+        latest.extend(receiver.try_iter());
+        for (repo_root, tasks) in latest {
+            if let Err(err) = save_tasks(&repo_root, tasks) {
+                warn!(
+                    "failed to persist tasks base_repo_path={} err={}",
+                    repo_root.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+fn save_tasks(repo_root: &Path, mut tasks: Vec<StoredTask>) -> Result<()> {
+    let path = task_store_path(repo_root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    tasks.sort_by_key(|task| task.summary.created_at);
+    let file = TaskStoreFile {
+        version: TASK_STORE_VERSION,
+        tasks,
+    };
+    let data = serde_json::to_string_pretty(&file).map_err(anyhow::Error::from)?;
+    // Write to a sibling file first so a crash mid-write never truncates the store.
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, data)?;
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
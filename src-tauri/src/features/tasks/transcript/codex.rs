@@ -0,0 +1,169 @@
+use super::{FileChangeKind, TranscriptBuilder};
+use crate::features::tasks::agents::sessions::parse_timestamp;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Codex injects its environment and AGENTS.md instructions as user messages.
+const CONTEXT_PREFIXES: [&str; 3] = ["<environment_context>", "<user_instructions>", "# AGENTS.md"];
+
+/// Rollouts wrap each model item as `{"type":"response_item","payload":{...}}`; older
+/// rollouts wrote the items bare. `event_msg` lines repeat the same content for the TUI.
+pub(super) fn parse(data: &str, builder: &mut TranscriptBuilder) {
+    for line in data.lines() {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let timestamp = str_field(&value, "timestamp").and_then(parse_timestamp);
+        let item = match str_field(&value, "type") {
+            Some("response_item") => match value.get("payload") {
+                Some(payload) => payload,
+                None => continue,
+            },
+            Some("session_meta" | "event_msg" | "turn_context" | "compacted") => continue,
+            _ => &value,
+        };
+        let call_id = str_field(item, "call_id").map(|value| value.to_string());
+        match str_field(item, "type") {
+            Some("message") => {
+                let text = message_text(item);
+                match str_field(item, "role") {
+                    Some("user") if !is_context_message(&text) => builder.prompt(timestamp, text),
+                    Some("assistant") => builder.message(timestamp, text),
+                    _ => {}
+                }
+            }
+            Some("function_call") => {
+                let name = str_field(item, "name").unwrap_or("tool").to_string();
+                let arguments = str_field(item, "arguments").unwrap_or_default();
+                let arguments: Option<Value> = serde_json::from_str(arguments).ok();
+                let command = arguments.as_ref().and_then(shell_command);
+                match command.as_deref().and_then(apply_patch_input) {
+                    Some(patch) => add_patch_edits(builder, timestamp, &call_id, patch),
+                    None => {
+                        let input = command.or_else(|| arguments.map(|value| value.to_string()));
+                        builder.tool_call(timestamp, call_id, name, input);
+                    }
+                }
+            }
+            Some("custom_tool_call") => {
+                let name = str_field(item, "name").unwrap_or("tool").to_string();
+                let input = str_field(item, "input").unwrap_or_default();
+                if name == "apply_patch" {
+                    add_patch_edits(builder, timestamp, &call_id, input);
+                } else {
+                    builder.tool_call(timestamp, call_id, name, Some(input.to_string()));
+                }
+            }
+            Some("local_shell_call") => {
+                let command = item.get("action").and_then(shell_command);
+                builder.tool_call(timestamp, call_id, "shell".to_string(), command);
+            }
+            Some("function_call_output" | "custom_tool_call_output") => {
+                if let (Some(call_id), Some(output)) = (call_id, item.get("output")) {
+                    let (text, success) = tool_output(output);
+                    builder.tool_output(&call_id, text, success);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(|value| value.as_str())
+}
+
+fn message_text(item: &Value) -> String {
+    let Some(content) = item.get("content").and_then(|value| value.as_array()) else {
+        return String::new();
+    };
+    content
+        .iter()
+        .filter_map(|part| str_field(part, "text"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn is_context_message(text: &str) -> bool {
+    let text = text.trim_start();
+    CONTEXT_PREFIXES.iter().any(|prefix| text.starts_with(prefix))
+}
+
+/// Shell tools take either an argv array or a single command string.
+fn shell_command(arguments: &Value) -> Option<String> {
+    match arguments.get("command")? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(argv) => {
+            let argv: Vec<&str> = argv.iter().filter_map(|arg| arg.as_str()).collect();
+            // `bash -lc "<script>"` is only noise around the script itself.
+            match argv.as_slice() {
+                [shell, flag, script] if shell.ends_with("sh") && flag.ends_with('c') => {
+                    Some(script.to_string())
+                }
+                _ => Some(argv.join(" ")),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Older models call `apply_patch` through the shell tool.
+fn apply_patch_input(command: &str) -> Option<&str> {
+    let patch = command.trim_start().strip_prefix("apply_patch")?;
+    let start = patch.find("*** Begin Patch")?;
+    Some(&patch[start..])
+}
+
+/// Splits an `apply_patch` envelope into one edit per file.
+fn add_patch_edits(
+    builder: &mut TranscriptBuilder,
+    timestamp: Option<DateTime<Utc>>,
+    call_id: &Option<String>,
+    patch: &str,
+) {
+    let mut current: Option<(String, FileChangeKind, Vec<&str>)> = None;
+    let mut edits = Vec::new();
+    for line in patch.lines() {
+        let header = [
+            ("*** Add File: ", FileChangeKind::Add),
+            ("*** Update File: ", FileChangeKind::Update),
+            ("*** Delete File: ", FileChangeKind::Delete),
+        ]
+        .into_iter()
+        .find_map(|(prefix, change)| line.strip_prefix(prefix).map(|path| (path, change)));
+        if let Some((path, change)) = header {
+            edits.extend(current.take());
+            current = Some((path.trim().to_string(), change, Vec::new()));
+        } else if line.starts_with("*** End Patch") || line.starts_with("*** Begin Patch") {
+            edits.extend(current.take());
+        } else if let Some((_, _, body)) = current.as_mut() {
+            body.push(line);
+        }
+    }
+    edits.extend(current);
+    for (path, change, body) in edits {
+        let patch = (!body.is_empty()).then(|| body.join("\n"));
+        builder.file_edit(timestamp, call_id.clone(), path, change, patch);
+    }
+}
+
+/// Shell output is JSON-encoded as `{"output": ..., "metadata": {"exit_code": ...}}`.
+fn tool_output(output: &Value) -> (String, Option<bool>) {
+    let text = match output {
+        Value::String(text) => text.clone(),
+        Value::Object(_) => str_field(output, "content").unwrap_or_default().to_string(),
+        other => other.to_string(),
+    };
+    let Ok(structured) = serde_json::from_str::<Value>(&text) else {
+        return (text, None);
+    };
+    let Some(inner) = str_field(&structured, "output") else {
+        return (text, None);
+    };
+    let success = structured
+        .get("metadata")
+        .and_then(|metadata| metadata.get("exit_code"))
+        .and_then(|code| code.as_i64())
+        .map(|code| code == 0);
+    (inner.to_string(), success)
+}
@@ -0,0 +1,3 @@
+pub mod task_transcript_get;
+pub mod task_transcript_watch_start;
+pub mod task_transcript_watch_stop;
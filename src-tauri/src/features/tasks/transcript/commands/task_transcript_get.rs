@@ -0,0 +1,25 @@
+use crate::commands::CommandResult;
+use crate::features::tasks::transcript::Transcript;
+use crate::features::tasks::TaskManager;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub task_id: Uuid,
+    /// Defaults to the worktree's latest session.
+    pub session_id: Option<String>,
+}
+
+pub type Response = Transcript;
+
+#[tauri::command]
+pub async fn task_transcript_get(
+    manager: tauri::State<'_, TaskManager>,
+    req: Request,
+) -> CommandResult<Response> {
+    manager
+        .get_transcript(req)
+        .map_err(|err| err.to_string())
+}
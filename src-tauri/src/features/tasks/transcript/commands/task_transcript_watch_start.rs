@@ -0,0 +1,23 @@
+use crate::commands::CommandResult;
+use crate::features::tasks::TaskManager;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub task_id: Uuid,
+}
+
+pub type Response = ();
+
+#[tauri::command]
+pub async fn task_transcript_watch_start(
+    manager: tauri::State<'_, TaskManager>,
+    app_handle: tauri::AppHandle,
+    req: Request,
+) -> CommandResult<Response> {
+    manager
+        .start_transcript_watch(req, &app_handle)
+        .map_err(|err| err.to_string())
+}
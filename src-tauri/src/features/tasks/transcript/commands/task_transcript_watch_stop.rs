@@ -0,0 +1,22 @@
+use crate::commands::CommandResult;
+use crate::features::tasks::TaskManager;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub task_id: Uuid,
+}
+
+pub type Response = ();
+
+#[tauri::command]
+pub async fn task_transcript_watch_stop(
+    manager: tauri::State<'_, TaskManager>,
+    req: Request,
+) -> CommandResult<Response> {
+    manager
+        .stop_transcript_watch(req)
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,102 @@
+use super::{FileChangeKind, TranscriptBuilder};
+use crate::features::tasks::agents::sessions::parse_timestamp;
+use serde_json::Value;
+
+/// Copilot CLI logs one event per line as `{"type": ..., "timestamp": ..., "data": {...}}`.
+/// Tool calls are taken from the execution events, which carry both arguments and results.
+pub(super) fn parse(data: &str, builder: &mut TranscriptBuilder) {
+    for line in data.lines() {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let timestamp = str_field(&value, "timestamp").and_then(parse_timestamp);
+        let Some(event) = value.get("data") else {
+            continue;
+        };
+        match str_field(&value, "type") {
+            Some("user.message") => {
+                let text = str_field(event, "content").unwrap_or_default();
+                builder.prompt(timestamp, text.to_string());
+            }
+            Some("assistant.message") => {
+                let text = str_field(event, "content").unwrap_or_default();
+                builder.message(timestamp, text.to_string());
+            }
+            Some("tool.execution_start") => {
+                let call_id = str_field(event, "toolCallId").map(|value| value.to_string());
+                let name = str_field(event, "toolName").unwrap_or("tool").to_string();
+                let arguments = tool_arguments(event);
+                match file_edit(&name, &arguments) {
+                    Some((path, change, patch)) => {
+                        builder.file_edit(timestamp, call_id, path, change, patch);
+                    }
+                    None => {
+                        let input = str_field(&arguments, "command")
+                            .map(|command| command.to_string())
+                            .or_else(|| (!arguments.is_null()).then(|| arguments.to_string()));
+                        builder.tool_call(timestamp, call_id, name, input);
+                    }
+                }
+            }
+            Some("tool.execution_complete") => {
+                let Some(call_id) = str_field(event, "toolCallId") else {
+                    continue;
+                };
+                let success = event.get("success").and_then(|value| value.as_bool());
+                let text = event
+                    .get("result")
+                    .and_then(|result| str_field(result, "content"))
+                    .or_else(|| {
+                        event
+                            .get("error")
+                            .and_then(|error| str_field(error, "message"))
+                    })
+                    .unwrap_or_default();
+                builder.tool_output(call_id, text.to_string(), success);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(|value| value.as_str())
+}
+
+/// Arguments are logged as an object, or as a JSON string by some releases.
+fn tool_arguments(event: &Value) -> Value {
+    match event.get("arguments") {
+        Some(Value::String(raw)) => {
+            serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.clone()))
+        }
+        Some(arguments) => arguments.clone(),
+        None => Value::Null,
+    }
+}
+
+/// Recognizes the file tools: `create`, `edit` and the older `str_replace_editor`.
+fn file_edit(name: &str, arguments: &Value) -> Option<(String, FileChangeKind, Option<String>)> {
+    let command = str_field(arguments, "command").unwrap_or(name);
+    if !matches!(name, "create" | "edit" | "str_replace_editor") || command == "view" {
+        return None;
+    }
+    let path = str_field(arguments, "path")?.to_string();
+    if command == "create" {
+        let patch = str_field(arguments, "file_text").map(|text| prefix_lines(text, '+'));
+        return Some((path, FileChangeKind::Add, patch));
+    }
+    let old = str_field(arguments, "old_str").map(|text| prefix_lines(text, '-'));
+    let new = str_field(arguments, "new_str").map(|text| prefix_lines(text, '+'));
+    let patch = match (old, new) {
+        (Some(old), Some(new)) => Some(format!("{}\n{}", old, new)),
+        (old, new) => old.or(new),
+    };
+    Some((path, FileChangeKind::Update, patch))
+}
+
+fn prefix_lines(text: &str, prefix: char) -> String {
+    text.lines()
+        .map(|line| format!("{}{}", prefix, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
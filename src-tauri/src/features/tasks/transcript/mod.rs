@@ -0,0 +1,334 @@
+pub mod commands;
+mod codex;
+mod copilot;
+
+use crate::features::tasks::agents::sessions::resolve_session_cwd;
+use crate::features::tasks::agents::{codex as codex_agent, copilot as copilot_agent};
+use crate::features::tasks::events::emit_transcript_changed;
+use crate::features::tasks::{AgentKind, AgentSession};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use log::warn;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Tool output beyond this many characters is cut off; the terminal still has all of it.
+const MAX_OUTPUT_CHARS: usize = 8000;
+
+/// What an agent did in one of its sessions, read from the agent's own session log.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    pub task_id: Uuid,
+    pub agent: AgentKind,
+    pub session_id: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub turns: Vec<TranscriptTurn>,
+}
+
+/// A user prompt and everything the agent did in response. Activity before the first
+/// prompt, such as a resumed session's replay, forms a turn without one.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptTurn {
+    pub prompt: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub items: Vec<TranscriptItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TranscriptItem {
+    Message {
+        timestamp: Option<DateTime<Utc>>,
+        text: String,
+    },
+    ToolCall {
+        timestamp: Option<DateTime<Utc>>,
+        call_id: Option<String>,
+        name: String,
+        input: Option<String>,
+        output: Option<String>,
+        success: Option<bool>,
+    },
+    FileEdit {
+        timestamp: Option<DateTime<Utc>>,
+        call_id: Option<String>,
+        path: String,
+        change: FileChangeKind,
+        patch: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FileChangeKind {
+    Add,
+    Update,
+    Delete,
+}
+
+#[derive(Default)]
+struct TranscriptBuilder {
+    turns: Vec<TranscriptTurn>,
+}
+
+impl TranscriptBuilder {
+    fn prompt(&mut self, timestamp: Option<DateTime<Utc>>, text: String) {
+        self.turns.push(TranscriptTurn {
+            prompt: Some(text),
+            started_at: timestamp,
+            items: Vec::new(),
+        });
+    }
+
+    fn message(&mut self, timestamp: Option<DateTime<Utc>>, text: String) {
+        if text.trim().is_empty() {
+            return;
+        }
+        self.push(TranscriptItem::Message { timestamp, text });
+    }
+
+    fn tool_call(
+        &mut self,
+        timestamp: Option<DateTime<Utc>>,
+        call_id: Option<String>,
+        name: String,
+        input: Option<String>,
+    ) {
+        self.push(TranscriptItem::ToolCall {
+            timestamp,
+            call_id,
+            name,
+            input,
+            output: None,
+            success: None,
+        });
+    }
+
+    /// Attaches a result to the most recent call with the same id.
+    fn tool_output(&mut self, call_id: &str, text: String, succeeded: Option<bool>) {
+        let call = self
+            .turns
+            .iter_mut()
+            .rev()
+            .flat_map(|turn| turn.items.iter_mut().rev())
+            .find(|item| {
+                matches!(item, TranscriptItem::ToolCall { call_id: Some(id), .. } if id == call_id)
+            });
+        if let Some(TranscriptItem::ToolCall { output, success, .. }) = call {
+            *output = Some(truncate_output(text));
+            *success = succeeded;
+        }
+    }
+
+    fn file_edit(
+        &mut self,
+        timestamp: Option<DateTime<Utc>>,
+        call_id: Option<String>,
+        path: String,
+        change: FileChangeKind,
+        patch: Option<String>,
+    ) {
+        self.push(TranscriptItem::FileEdit {
+            timestamp,
+            call_id,
+            path,
+            change,
+            patch,
+        });
+    }
+
+    fn push(&mut self, item: TranscriptItem) {
+        if self.turns.is_empty() {
+            self.turns.push(TranscriptTurn {
+                prompt: None,
+                started_at: item_timestamp(&item),
+                items: Vec::new(),
+            });
+        }
+        if let Some(turn) = self.turns.last_mut() {
+            turn.items.push(item);
+        }
+    }
+
+    fn finish(self) -> Vec<TranscriptTurn> {
+        self.turns
+    }
+}
+
+fn item_timestamp(item: &TranscriptItem) -> Option<DateTime<Utc>> {
+    match item {
+        TranscriptItem::Message { timestamp, .. }
+        | TranscriptItem::ToolCall { timestamp, .. }
+        | TranscriptItem::FileEdit { timestamp, .. } => *timestamp,
+    }
+}
+
+fn truncate_output(text: String) -> String {
+    match text.char_indices().nth(MAX_OUTPUT_CHARS) {
+        Some((index, _)) => format!("{}\n…", &text[..index]),
+        None => text,
+    }
+}
+
+/// Agents whose session logs can be read back as a transcript.
+#[derive(Clone, Copy)]
+enum SessionSource {
+    Codex,
+    Copilot,
+}
+
+impl SessionSource {
+    fn for_agent(agent: &AgentKind) -> Option<Self> {
+        match agent {
+            AgentKind::Codex => Some(SessionSource::Codex),
+            AgentKind::Copilot => Some(SessionSource::Copilot),
+            AgentKind::Aider | AgentKind::Custom(_) => None,
+        }
+    }
+
+    fn list_sessions(self, worktree_path: &Path) -> anyhow::Result<Vec<AgentSession>> {
+        match self {
+            SessionSource::Codex => codex_agent::list_sessions(worktree_path),
+            SessionSource::Copilot => copilot_agent::list_sessions(worktree_path),
+        }
+    }
+
+    fn sessions_dir(self, worktree_path: &Path) -> anyhow::Result<PathBuf> {
+        match self {
+            SessionSource::Codex => codex_agent::sessions_dir(worktree_path),
+            SessionSource::Copilot => copilot_agent::sessions_dir(worktree_path),
+        }
+    }
+
+    fn read_session(self, path: &Path, desired_cwd: &str) -> Option<AgentSession> {
+        match self {
+            SessionSource::Codex => codex_agent::read_session(path, desired_cwd),
+            SessionSource::Copilot => copilot_agent::read_session(path, desired_cwd),
+        }
+    }
+
+    /// Codex nests rollouts in dated directories; Copilot keeps a flat directory.
+    fn recursive_mode(self) -> RecursiveMode {
+        match self {
+            SessionSource::Codex => RecursiveMode::Recursive,
+            SessionSource::Copilot => RecursiveMode::NonRecursive,
+        }
+    }
+
+    fn parse(self, data: &str, builder: &mut TranscriptBuilder) {
+        match self {
+            SessionSource::Codex => codex::parse(data, builder),
+            SessionSource::Copilot => copilot::parse(data, builder),
+        }
+    }
+}
+
+fn unsupported(agent: &AgentKind) -> anyhow::Error {
+    anyhow::anyhow!("{} does not keep a session log to read a transcript from.", agent.id())
+}
+
+/// Reads `session_id`, or the worktree's latest session, into a transcript. A worktree
+/// without sessions gets an empty transcript.
+pub fn load_transcript(
+    task_id: Uuid,
+    agent: &AgentKind,
+    worktree_path: &Path,
+    session_id: Option<&str>,
+) -> anyhow::Result<Transcript> {
+    let source = SessionSource::for_agent(agent).ok_or_else(|| unsupported(agent))?;
+    let sessions = source.list_sessions(worktree_path)?;
+    let session = match session_id {
+        Some(session_id) => Some(
+            sessions
+                .into_iter()
+                .find(|session| session.session_id == session_id)
+                .with_context(|| format!("session {} not found for this task", session_id))?,
+        ),
+        None => sessions.into_iter().next(),
+    };
+    let Some(session) = session else {
+        return Ok(Transcript {
+            task_id,
+            agent: agent.clone(),
+            session_id: None,
+            updated_at: None,
+            turns: Vec::new(),
+        });
+    };
+    let data = fs::read_to_string(&session.path)
+        .with_context(|| format!("failed to read {}", session.path.display()))?;
+    let mut builder = TranscriptBuilder::default();
+    source.parse(&data, &mut builder);
+    Ok(Transcript {
+        task_id,
+        agent: agent.clone(),
+        session_id: Some(session.session_id),
+        updated_at: session.updated_at,
+        turns: builder.finish(),
+    })
+}
+
+/// Emits `task_transcript_changed` when a session log belonging to the task's worktree
+/// is written, including sessions started after the watch began.
+pub struct TranscriptWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl TranscriptWatcher {
+    pub fn new(
+        task_id: Uuid,
+        agent: &AgentKind,
+        worktree_path: &Path,
+        app: tauri::AppHandle,
+    ) -> anyhow::Result<Self> {
+        let source = SessionSource::for_agent(agent).ok_or_else(|| unsupported(agent))?;
+        let desired_cwd = resolve_session_cwd(worktree_path)?;
+        let dir = source.sessions_dir(worktree_path)?;
+        // The agent may not have recorded anything yet; watch the directory regardless.
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        // Whether a log belongs to this worktree never changes once it has content.
+        let known_logs: Mutex<HashMap<PathBuf, bool>> = Mutex::new(HashMap::new());
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            match res {
+                Ok(event) => {
+                    if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                        return;
+                    }
+                    let mut known_logs = known_logs.lock();
+                    let belongs = event.paths.iter().any(|path| {
+                        if let Some(belongs) = known_logs.get(path) {
+                            return *belongs;
+                        }
+                        let belongs = source.read_session(path, &desired_cwd).is_some();
+                        let has_content = fs::metadata(path)
+                            .map(|metadata| metadata.is_file() && metadata.len() > 0)
+                            .unwrap_or(false);
+                        if belongs || has_content {
+                            known_logs.insert(path.clone(), belongs);
+                        }
+                        belongs
+                    });
+                    if belongs {
+                        emit_transcript_changed(&app, task_id);
+                    }
+                }
+                Err(err) => {
+                    warn!("transcript watch error task_id={} err={}", task_id, err);
+                }
+            }
+        })
+        .with_context(|| format!("failed to create transcript watcher for {}", dir.display()))?;
+        watcher
+            .watch(&dir, source.recursive_mode())
+            .with_context(|| format!("failed to watch {}", dir.display()))?;
+        Ok(Self { _watcher: watcher })
+    }
+}
@@ -1,13 +1,181 @@
-use crate::error::Result;
+use crate::error::{Result, TaskError};
+use crate::features::tasks::config::{
+    illuc_dir, load_global_config, load_repo_config, resolve_config_path,
+};
+use crate::features::tasks::git::{
+    ensure_excluded, find_remote_branches, list_worktrees, ref_exists,
+};
+use crate::features::tasks::store::repo_key;
+use crate::features::tasks::BranchNameError;
+use log::warn;
+use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
 
+/// How the task's branch gets into its new worktree.
+pub enum BranchCheckout {
+    /// Create `branch` from `base_ref`.
+    New { branch: String, base_ref: String },
+    Local(String),
+    /// Create `local` tracking `remote_ref` (e.g. `origin/feature`).
+    Remote { local: String, remote_ref: String },
+}
+
+impl BranchCheckout {
+    pub fn local_name(&self) -> &str {
+        match self {
+            BranchCheckout::New { branch, .. } => branch,
+            BranchCheckout::Local(name) => name,
+            BranchCheckout::Remote { local, .. } => local,
+        }
+    }
+
+    /// Whether the branch is created by the task and may be deleted with it.
+    pub fn owns_branch(&self) -> bool {
+        !matches!(self, BranchCheckout::Local(_))
+    }
+
+    /// Registers the worktree without populating it; files are checked out separately.
+    pub fn worktree_add_args(&self, worktree_path: &str) -> Vec<String> {
+        let mut args = vec![
+            "worktree".to_string(),
+            "add".to_string(),
+            "--no-checkout".to_string(),
+        ];
+        match self {
+            BranchCheckout::New { branch, base_ref } => {
+                args.extend(["-b".to_string(), branch.clone()]);
+                args.push(worktree_path.to_string());
+                args.push(base_ref.clone());
+            }
+            BranchCheckout::Local(name) => {
+                args.push(worktree_path.to_string());
+                args.push(name.clone());
+            }
+            BranchCheckout::Remote { local, remote_ref } => {
+                args.extend(["--track".to_string(), "-b".to_string(), local.clone()]);
+                args.push(worktree_path.to_string());
+                args.push(remote_ref.clone());
+            }
+        }
+        args
+    }
+}
+
+/// Resolves `name` as a local branch, an explicit `<remote>/<branch>`, or a
+/// branch that exists on exactly one remote.
+pub fn resolve_existing_branch(repo_root: &Path, name: &str) -> Result<BranchCheckout> {
+    let name = clean_branch_name(name);
+    if ref_exists(repo_root, &format!("refs/heads/{}", name)) {
+        return Ok(BranchCheckout::Local(name));
+    }
+    if ref_exists(repo_root, &format!("refs/remotes/{}", name)) {
+        let local = name
+            .split_once('/')
+            .map(|(_, branch)| branch.to_string())
+            .unwrap_or_else(|| name.clone());
+        if ref_exists(repo_root, &format!("refs/heads/{}", local)) {
+            return Err(TaskError::Message(format!(
+                "Local branch '{}' already exists; select it instead of '{}'.",
+                local, name
+            )));
+        }
+        return Ok(BranchCheckout::Remote {
+            local,
+            remote_ref: name,
+        });
+    }
+    let mut remote_refs = find_remote_branches(repo_root, &name)?;
+    match remote_refs.len() {
+        0 => Err(TaskError::Message(format!(
+            "Branch '{}' does not exist locally or on any remote.",
+            name
+        ))),
+        1 => Ok(BranchCheckout::Remote {
+            local: name,
+            remote_ref: remote_refs.remove(0),
+        }),
+        _ => Err(TaskError::Message(format!(
+            "Branch '{}' exists on several remotes ({}); pick one explicitly.",
+            name,
+            remote_refs.join(", ")
+        ))),
+    }
+}
+
+/// Cleans user-entered sparse-checkout cone paths into repo-relative directories.
+pub fn normalize_sparse_paths(paths: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized = Vec::new();
+    for path in paths {
+        let cleaned = path.trim().replace('\\', "/");
+        let cleaned = cleaned.trim_matches('/');
+        if cleaned.is_empty() {
+            continue;
+        }
+        if cleaned.split('/').any(|part| part == ".." || part == ".") {
+            return Err(TaskError::Message(format!(
+                "Sparse path '{}' must be relative to the repository root.",
+                path.trim()
+            )));
+        }
+        normalized.push(cleaned.to_string());
+    }
+    normalized.sort();
+    normalized.dedup();
+    Ok(normalized)
+}
+
+/// Git refuses to check a branch out twice; report which worktree holds it.
+pub fn ensure_branch_available(repo_root: &Path, branch: &str) -> Result<()> {
+    let full_ref = format!("refs/heads/{}", branch);
+    let holder = list_worktrees(repo_root)?
+        .into_iter()
+        .find(|entry| entry.branch.as_deref() == Some(full_ref.as_str()));
+    match holder {
+        Some(entry) => Err(BranchNameError::CheckedOut {
+            name: branch.to_string(),
+            worktree: entry.path.display().to_string(),
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Where new task worktrees go: the per-repo `worktreeRoot`, then the global one
+/// (each repo gets its own subfolder there), then `<repo>/.illuc/worktrees`.
 pub fn managed_worktree_root(repo_root: &Path) -> Result<PathBuf> {
-    let illuc_dir = repo_root.join(".illuc");
-    let worktree_dir = illuc_dir.join("worktrees");
+    // `.illuc/` holds the task store even when worktrees live elsewhere.
+    if let Err(err) = ensure_excluded(repo_root, "/.illuc/") {
+        warn!("failed to exclude .illuc repo={} err={}", repo_root.display(), err);
+    }
+    let repo_config = load_repo_config(repo_root)?;
+    let worktree_dir = match repo_config.worktree_root {
+        Some(root) => resolve_config_path(&root, repo_root),
+        None => match load_global_config()?.worktree_root {
+            Some(root) => {
+                let home = dirs::home_dir().unwrap_or_default();
+                resolve_config_path(&root, &home).join(repo_folder_name(repo_root))
+            }
+            None => default_worktree_root(repo_root),
+        },
+    };
     if !worktree_dir.exists() {
         std::fs::create_dir_all(&worktree_dir)?;
     }
-    Ok(worktree_dir)
+    Ok(worktree_dir.canonicalize().unwrap_or(worktree_dir))
+}
+
+pub fn default_worktree_root(repo_root: &Path) -> PathBuf {
+    illuc_dir(repo_root).join("worktrees")
+}
+
+/// `<repo name>-<hash of its path>`, so same-named repos never share a folder.
+fn repo_folder_name(repo_root: &Path) -> String {
+    let digest = Sha256::digest(repo_key(repo_root).as_bytes());
+    let hash: String = format!("{:x}", digest).chars().take(12).collect();
+    match repo_root.file_name() {
+        Some(name) => format!("{}-{}", name.to_string_lossy(), hash),
+        None => hash,
+    }
 }
 
 pub fn clean_branch_name(branch: &str) -> String {
@@ -12,8 +12,12 @@ use crate::features::tasks::git::commands::task_git_diff_watch_start::task_git_d
 use crate::features::tasks::git::commands::task_git_diff_watch_stop::task_git_diff_watch_stop;
 use crate::features::tasks::git::commands::task_git_list_branches::task_git_list_branches;
 use crate::features::tasks::git::commands::task_git_push::task_git_push;
+use crate::features::tasks::git::commands::task_git_sparse_set::task_git_sparse_set;
 use crate::features::tasks::management::commands::select_base_repo::select_base_repo;
+use crate::features::tasks::management::commands::task_agent_sessions_list::task_agent_sessions_list;
+use crate::features::tasks::management::commands::task_approval_respond::task_approval_respond;
 use crate::features::tasks::management::commands::task_create::task_create;
+use crate::features::tasks::management::commands::task_create_cancel::task_create_cancel;
 use crate::features::tasks::management::commands::task_discard::task_discard;
 use crate::features::tasks::management::commands::task_load_existing::task_load_existing;
 use crate::features::tasks::management::commands::task_open_worktree_in_vscode::task_open_worktree_in_vscode;
@@ -23,6 +27,9 @@ use crate::features::tasks::management::commands::task_stop::task_stop;
 use crate::features::tasks::management::commands::task_terminal_start::task_terminal_start;
 use crate::features::tasks::management::commands::task_terminal_resize::task_terminal_resize;
 use crate::features::tasks::management::commands::task_terminal_write::task_terminal_write;
+use crate::features::tasks::transcript::commands::task_transcript_get::task_transcript_get;
+use crate::features::tasks::transcript::commands::task_transcript_watch_start::task_transcript_watch_start;
+use crate::features::tasks::transcript::commands::task_transcript_watch_stop::task_transcript_watch_stop;
 use crate::features::tasks::TaskManager;
 use log::info;
 
@@ -43,8 +50,11 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             select_base_repo,
             task_create,
+            task_create_cancel,
             task_start,
             task_stop,
+            task_agent_sessions_list,
+            task_approval_respond,
             task_discard,
             task_terminal_write,
             task_terminal_resize,
@@ -54,6 +64,10 @@ pub fn run() {
             task_git_diff_watch_stop,
             task_git_commit,
             task_git_push,
+            task_git_sparse_set,
+            task_transcript_get,
+            task_transcript_watch_start,
+            task_transcript_watch_stop,
             task_load_existing,
             task_open_worktree_in_vscode,
             task_open_worktree_terminal,
@@ -1,5 +1,6 @@
 pub mod fs;
 pub mod path;
+pub mod process;
 pub mod screen;
 #[cfg(target_os = "windows")]
 pub mod windows;
@@ -0,0 +1,43 @@
+//! Signals for the process group of a PTY child. Children spawned on a PTY lead their
+//! own session, so the group id is the child's pid. Off Unix these do nothing, the
+//! child handle's own `kill` is all there is, and whether the group is gone is unknown.
+
+/// Sends SIGINT to every process in the group led by `pid`.
+pub fn interrupt_group(pid: u32) {
+    #[cfg(unix)]
+    signal_group(pid, libc::SIGINT);
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Sends SIGKILL to every process in the group led by `pid`, including grandchildren
+/// such as dev servers that outlive the agent.
+pub fn kill_group(pid: u32) {
+    #[cfg(unix)]
+    signal_group(pid, libc::SIGKILL);
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Whether any process in the group led by `pid` is still running, or `None` where
+/// process groups cannot be observed.
+pub fn group_alive(pid: u32) -> Option<bool> {
+    #[cfg(unix)]
+    {
+        Some(signal_group(pid, 0))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: libc::c_int) -> bool {
+    let Ok(pgid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: killpg has no memory-safety requirements; a stale group id only fails.
+    unsafe { libc::killpg(pgid, signal) == 0 }
+}
@@ -22,4 +22,23 @@ impl Screen {
     pub fn full_text(&self) -> String {
         self.parser.screen().contents()
     }
+
+    /// Text of the row holding the cursor, which is where a pending prompt waits.
+    pub fn cursor_line(&self) -> String {
+        self.lines()
+            .into_iter()
+            .nth(self.cursor_row())
+            .unwrap_or_default()
+    }
+
+    /// Text of every visible row, without joining wrapped rows.
+    pub fn lines(&self) -> Vec<String> {
+        let screen = self.parser.screen();
+        let (_, cols) = screen.size();
+        screen.rows(0, cols).collect()
+    }
+
+    pub fn cursor_row(&self) -> usize {
+        self.parser.screen().cursor_position().0 as usize
+    }
 }
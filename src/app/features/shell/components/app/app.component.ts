@@ -27,6 +27,7 @@ import { LoadingButtonComponent } from "../../../../shared/components/loading-bu
 export class AppComponent implements OnInit, OnDestroy {
     showCreateModal = false;
     branchNameInput = "";
+    promptInput = "";
     branchNameError = "";
     confirmDiscardTaskId: string | null = null;
     confirmDiscardTitle = "";
@@ -125,6 +126,7 @@ export class AppComponent implements OnInit, OnDestroy {
     closeCreateTaskModal(): void {
         this.showCreateModal = false;
         this.branchNameInput = "";
+        this.promptInput = "";
         this.branchNameError = "";
         this.baseBranchSelection = "";
     }
@@ -150,6 +152,7 @@ export class AppComponent implements OnInit, OnDestroy {
                 branch,
                 title,
                 this.baseBranchSelection,
+                this.promptInput,
             );
             this.closeCreateTaskModal();
         } catch (error: unknown) {
@@ -185,7 +188,10 @@ export class AppComponent implements OnInit, OnDestroy {
         }
         this.stoppingTaskIds.add(taskId);
         try {
-            await this.taskStore.stopTask(taskId);
+            const outcome = await this.taskStore.stopTask(taskId);
+            if (outcome.exited === false) {
+                console.warn(`Agent processes for task ${taskId} are still running.`);
+            }
         } catch (error: unknown) {
             console.error(this.describeError(error, "Unable to stop task."));
         } finally {
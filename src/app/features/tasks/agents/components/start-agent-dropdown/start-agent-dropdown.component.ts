@@ -27,6 +27,7 @@ export class StartAgentDropdownComponent implements OnChanges {
     readonly options = [
         { kind: AgentKind.Codex, label: "Codex" },
         { kind: AgentKind.Copilot, label: "Copilot" },
+        { kind: AgentKind.Aider, label: "Aider" },
     ];
 
     toggleMenu(event: MouseEvent): void {
@@ -1,5 +1,6 @@
 export type TaskStatus =
     | "CREATING_WORKTREE"
+    | "SETTING_UP"
     | "IDLE"
     | "AWAITING_APPROVAL"
     | "WORKING"
@@ -11,8 +12,11 @@ export type TaskStatus =
 export enum AgentKind {
     Codex = "codex",
     Copilot = "copilot",
+    Aider = "aider",
 }
 
+export type BranchMode = "new" | "existing";
+
 export interface TaskSummary {
     taskId: string;
     title: string;
@@ -22,10 +26,109 @@ export interface TaskSummary {
     endedAt?: string | null;
     worktreePath: string;
     branchName: string;
+    branchMode: BranchMode;
     baseBranch: string;
     baseRepoPath: string;
     baseCommit: string;
+    sparsePaths: string[];
+    prompt?: string | null;
     exitCode?: number | null;
+    failureReason?: FailureReason | null;
+    launchOptions?: AgentLaunchOptions | null;
+    budgets?: AgentBudgets | null;
+    stopReason?: StopReason | null;
+    restartAttempts?: number;
+    history: TaskHistoryEntry[];
+}
+
+export interface StopOutcome {
+    task: TaskSummary;
+    graceful: boolean;
+    exited: boolean | null;
+}
+
+export type ReasoningEffort = "minimal" | "low" | "medium" | "high";
+
+export type SandboxMode = "readOnly" | "workspaceWrite" | "fullAuto";
+
+export interface AgentLaunchOptions {
+    model?: string | null;
+    effort?: ReasoningEffort | null;
+    profile?: string | null;
+    sandbox?: SandboxMode | null;
+    extraArgs?: string[];
+}
+
+export interface AgentBudgets {
+    maxRuntimeSecs?: number | null;
+    maxWorkingSecs?: number | null;
+    maxAwaitingApprovalSecs?: number | null;
+}
+
+export type Budget = "runtime" | "working" | "awaitingApproval";
+
+export type StopReason =
+    | { kind: "user" }
+    | { kind: "budgetExceeded"; budget: Budget; limitSecs: number };
+
+export type RestartPolicy =
+    | { mode: "never" }
+    | { mode: "onFailure"; maxRetries: number; backoffMs?: number };
+
+export type AgentFailure =
+    | "binaryNotFound"
+    | "notAuthenticated"
+    | "rateLimited"
+    | "networkUnreachable"
+    | "crashed";
+
+export interface FailureReason {
+    failure: AgentFailure;
+    hint: string;
+}
+
+export type TaskHistoryEvent =
+    | {
+          kind: "autoApproval";
+          command: string;
+          decision: ApprovalDecision;
+          rule: string;
+      }
+    | {
+          kind: "unenforcedRules";
+          agent: AgentKind;
+          rules: string[];
+      }
+    | {
+          kind: "budgetExceeded";
+          budget: Budget;
+          limitSecs: number;
+      }
+    | {
+          kind: "agentRestart";
+          attempt: number;
+          maxRetries: number;
+          exitCode: number;
+          delayMs: number;
+      }
+    | {
+          kind: "restartAbandoned";
+          failure: AgentFailure;
+          attempts: number;
+      };
+
+export type TaskHistoryEntry = { at: string } & TaskHistoryEvent;
+
+export interface AgentSession {
+    sessionId: string;
+    startedAt?: string | null;
+    updatedAt?: string | null;
+    path: string;
+}
+
+export interface AgentSessionChoice {
+    sessionId?: string;
+    newSession?: boolean;
 }
 
 export interface BaseRepoInfo {
@@ -35,9 +138,25 @@ export interface BaseRepoInfo {
     head: string;
 }
 
+export type BranchNameError =
+    | { kind: "missing" }
+    | { kind: "missingPlaceholder"; template: string; placeholder: string }
+    | { kind: "invalid"; name: string; reason: string }
+    | { kind: "exists"; name: string }
+    | { kind: "conflict"; name: string; existing: string }
+    | { kind: "checkedOut"; name: string; worktree: string };
+
+export interface CreateTaskError {
+    message: string;
+    branch: BranchNameError | null;
+}
+
+export type DiffFileKind = "file" | "submodule";
+
 export interface DiffFile {
     path: string;
     status: string;
+    kind: DiffFileKind;
 }
 
 export type DiffMode = "worktree" | "branch";
@@ -48,11 +167,59 @@ export interface DiffPayload {
     unifiedDiff: string;
 }
 
+export type ApprovalDecision = "approve" | "approveAlways" | "deny";
+
+export interface ApprovalRequestedEvent {
+    taskId: string;
+    agent: AgentKind;
+    command?: string | null;
+}
+
 export interface DiffChangedEvent {
     taskId: string;
 }
 
-export type TerminalKind = "agent" | "worktree";
+export type FileChangeKind = "add" | "update" | "delete";
+
+export type TranscriptItem =
+    | { kind: "message"; timestamp?: string | null; text: string }
+    | {
+          kind: "toolCall";
+          timestamp?: string | null;
+          callId?: string | null;
+          name: string;
+          input?: string | null;
+          output?: string | null;
+          success?: boolean | null;
+      }
+    | {
+          kind: "fileEdit";
+          timestamp?: string | null;
+          callId?: string | null;
+          path: string;
+          change: FileChangeKind;
+          patch?: string | null;
+      };
+
+export interface TranscriptTurn {
+    prompt?: string | null;
+    startedAt?: string | null;
+    items: TranscriptItem[];
+}
+
+export interface Transcript {
+    taskId: string;
+    agent: AgentKind;
+    sessionId?: string | null;
+    updatedAt?: string | null;
+    turns: TranscriptTurn[];
+}
+
+export interface TranscriptChangedEvent {
+    taskId: string;
+}
+
+export type TerminalKind = "agent" | "worktree" | "setup";
 
 export interface TerminalOutputEvent {
     taskId: string;
@@ -65,3 +232,14 @@ export interface TerminalExitEvent {
     exitCode: number;
     kind: TerminalKind;
 }
+
+export interface BudgetExceededEvent {
+    taskId: string;
+    budget: Budget;
+    limitSecs: number;
+}
+
+export interface WorktreeProgressEvent {
+    taskId: string;
+    message: string;
+}
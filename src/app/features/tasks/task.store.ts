@@ -3,14 +3,25 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { Observable, Subject } from "rxjs";
 import {
+    AgentBudgets,
     AgentKind,
+    AgentLaunchOptions,
+    AgentSession,
+    AgentSessionChoice,
+    ApprovalDecision,
+    ApprovalRequestedEvent,
     BaseRepoInfo,
+    BudgetExceededEvent,
     DiffMode,
     DiffPayload,
+    RestartPolicy,
     TerminalKind,
     TerminalExitEvent,
     TerminalOutputEvent,
+    StopOutcome,
     TaskSummary,
+    Transcript,
+    WorktreeProgressEvent,
 } from "./task.models";
 import { TaskGitService } from "./git/task-git.service";
 import { TERMINAL_SCROLLBACK } from "./terminal.constants";
@@ -24,6 +35,9 @@ export class TaskStore {
     private readonly baseRepoSignal = signal<BaseRepoInfo | null>(null);
     private readonly selectedTaskIdSignal = signal<string | null>(null);
     private readonly branchOptionsSignal = signal<string[]>([]);
+    private readonly pendingApprovalsSignal = signal<
+        Record<string, ApprovalRequestedEvent>
+    >({});
     private readonly terminalBuffers = new Map<string, string>();
     private readonly terminalStreams = new Map<string, Subject<string>>();
     private readonly terminalSizes = new Map<
@@ -51,6 +65,7 @@ export class TaskStore {
     readonly baseRepo = this.baseRepoSignal.asReadonly();
     readonly selectedTaskId = this.selectedTaskIdSignal.asReadonly();
     readonly branchOptions = this.branchOptionsSignal.asReadonly();
+    readonly pendingApprovals = this.pendingApprovalsSignal.asReadonly();
     readonly selectedTask = computed(() => {
         const id = this.selectedTaskIdSignal();
         if (!id) {
@@ -91,6 +106,7 @@ export class TaskStore {
         branchName: string,
         displayTitle: string,
         baseBranch?: string | null,
+        prompt?: string | null,
     ): Promise<TaskSummary> {
         const repo = this.baseRepoSignal();
         if (!repo) {
@@ -102,7 +118,8 @@ export class TaskStore {
                 baseRepoPath: repo.path,
                 baseRef,
                 taskTitle: displayTitle.trim() || undefined,
-                branchName: branchName.trim(),
+                branchName: branchName.trim() || undefined,
+                prompt: prompt?.trim() || undefined,
             },
         });
         this.upsertTask(summary);
@@ -110,7 +127,19 @@ export class TaskStore {
         return summary;
     }
 
-    async startTask(taskId: string, agent?: AgentKind): Promise<TaskSummary> {
+    async cancelTaskCreation(taskId: string): Promise<void> {
+        await invoke("task_create_cancel", { req: { taskId } });
+    }
+
+    async startTask(
+        taskId: string,
+        agent?: AgentKind,
+        prompt?: string,
+        session?: AgentSessionChoice,
+        launchOptions?: AgentLaunchOptions,
+        budgets?: AgentBudgets,
+        restartPolicy?: RestartPolicy,
+    ): Promise<TaskSummary> {
         const size = this.terminalSizes.get(taskId) ?? this.lastTerminalSize;
         const summary = await invoke<TaskSummary>("task_start", {
             req: {
@@ -118,18 +147,42 @@ export class TaskStore {
                 cols: size?.cols,
                 rows: size?.rows,
                 agent,
+                prompt: prompt?.trim() || undefined,
+                sessionId: session?.sessionId,
+                newSession: session?.newSession ?? false,
+                launchOptions,
+                budgets,
+                restartPolicy,
             },
         });
         this.upsertTask(summary);
         return summary;
     }
 
-    async stopTask(taskId: string): Promise<TaskSummary> {
-        const summary = await invoke<TaskSummary>("task_stop", {
-            req: { taskId },
+    async listAgentSessions(
+        taskId: string,
+        agent?: AgentKind,
+    ): Promise<AgentSession[]> {
+        return invoke<AgentSession[]>("task_agent_sessions_list", {
+            req: { taskId, agent },
+        });
+    }
+
+    async respondToApproval(
+        taskId: string,
+        decision: ApprovalDecision,
+    ): Promise<void> {
+        await invoke("task_approval_respond", {
+            req: { taskId, decision },
         });
-        this.upsertTask(summary);
-        return summary;
+    }
+
+    async stopTask(taskId: string, gracePeriodMs?: number): Promise<StopOutcome> {
+        const outcome = await invoke<StopOutcome>("task_stop", {
+            req: { taskId, gracePeriodMs },
+        });
+        this.upsertTask(outcome.task);
+        return outcome;
     }
 
     async discardTask(taskId: string): Promise<void> {
@@ -212,6 +265,23 @@ export class TaskStore {
         await invoke("task_git_diff_watch_stop", { req: { taskId } });
     }
 
+    async getTranscript(
+        taskId: string,
+        sessionId?: string,
+    ): Promise<Transcript> {
+        return invoke<Transcript>("task_transcript_get", {
+            req: { taskId, sessionId },
+        });
+    }
+
+    async startTranscriptWatch(taskId: string): Promise<void> {
+        await invoke("task_transcript_watch_start", { req: { taskId } });
+    }
+
+    async stopTranscriptWatch(taskId: string): Promise<void> {
+        await invoke("task_transcript_watch_stop", { req: { taskId } });
+    }
+
     watchDiff(taskId: string, mode: DiffMode): DiffWatchHandle {
         const watcher = new DiffWatcher({
             taskId,
@@ -321,10 +391,44 @@ export class TaskStore {
     private registerEventListeners(): void {
         void listen<TaskSummary>("task_status_changed", (event) => {
             this.zone.run(() => {
+                if (event.payload.status !== "AWAITING_APPROVAL") {
+                    this.clearPendingApproval(event.payload.taskId);
+                }
+                if (event.payload.status === "DISCARDED") {
+                    this.removeTask(event.payload.taskId);
+                    return;
+                }
                 this.upsertTask(event.payload);
             });
         }).then((unlisten) => this.unlistenFns.push(unlisten));
 
+        void listen<ApprovalRequestedEvent>("task_approval_requested", (event) => {
+            this.zone.run(() => {
+                this.pendingApprovalsSignal.update((approvals) => ({
+                    ...approvals,
+                    [event.payload.taskId]: event.payload,
+                }));
+            });
+        }).then((unlisten) => this.unlistenFns.push(unlisten));
+
+        void listen<BudgetExceededEvent>("task_budget_exceeded", (event) => {
+            this.zone.run(() => {
+                console.warn(
+                    `Task ${event.payload.taskId} ran past its ${event.payload.budget} budget of ${event.payload.limitSecs}s and is being stopped.`,
+                );
+            });
+        }).then((unlisten) => this.unlistenFns.push(unlisten));
+
+        void listen<WorktreeProgressEvent>("task_worktree_progress", (event) => {
+            this.zone.run(() => {
+                this.pushTerminalOutput(
+                    event.payload.taskId,
+                    `${event.payload.message}\r\n`,
+                    "agent",
+                );
+            });
+        }).then((unlisten) => this.unlistenFns.push(unlisten));
+
         void listen<TerminalOutputEvent>("task_terminal_output", (event) => {
             this.zone.run(() => {
                 this.pushTerminalOutput(
@@ -344,6 +448,16 @@ export class TaskStore {
         }).then((unlisten) => this.unlistenFns.push(unlisten));
     }
 
+    private clearPendingApproval(taskId: string): void {
+        if (!(taskId in this.pendingApprovalsSignal())) {
+            return;
+        }
+        this.pendingApprovalsSignal.update((approvals) => {
+            const { [taskId]: _, ...rest } = approvals;
+            return rest;
+        });
+    }
+
     private upsertTask(summary: TaskSummary): void {
         this.tasksSignal.update((items) => {
             const existingIndex = items.findIndex(
@@ -9,7 +9,14 @@ import {
     NgZone,
 } from "@angular/core";
 import { FormsModule } from "@angular/forms";
-import { AgentKind, TaskSummary, BaseRepoInfo } from "../../../task.models";
+import {
+    AgentFailure,
+    AgentKind,
+    TaskSummary,
+    BaseRepoInfo,
+    Budget,
+    TaskHistoryEntry,
+} from "../../../task.models";
 import { parseTitleParts, TitleParts } from "../../../title.utils";
 import { TaskTerminalComponent } from "../../../terminal/components/task-terminal/task-terminal.component";
 import { TaskDiffComponent } from "../../../git/components/task-diff/task-diff.component";
@@ -90,6 +97,50 @@ export class TaskViewComponent {
         return this.task?.status.replace(/_/g, " ") ?? "";
     }
 
+    history(): TaskHistoryEntry[] {
+        return [...(this.task?.history ?? [])].reverse();
+    }
+
+    historyLabel(entry: TaskHistoryEntry): string {
+        switch (entry.kind) {
+            case "autoApproval": {
+                const verb = entry.decision === "deny" ? "Denied" : "Approved";
+                return `${verb} \`${entry.command}\` by rule ${entry.rule}`;
+            }
+            case "unenforcedRules":
+                return `${entry.agent} cannot enforce forbidden ${entry.rules.join(", ")}`;
+            case "budgetExceeded":
+                return `Stopped after the ${this.budgetLabel(entry.budget)} budget of ${entry.limitSecs}s ran out`;
+            case "agentRestart":
+                return `Exited with code ${entry.exitCode}, restarting in ${entry.delayMs / 1000}s (attempt ${entry.attempt} of ${entry.maxRetries})`;
+            case "restartAbandoned":
+                return `Gave up restarting after ${entry.attempts} attempt(s): ${this.failureLabel(entry.failure)}`;
+        }
+    }
+
+    failureLabel(failure: AgentFailure): string {
+        switch (failure) {
+            case "binaryNotFound":
+                return "agent not installed";
+            case "notAuthenticated":
+                return "agent not logged in";
+            case "rateLimited":
+                return "rate limited";
+            case "networkUnreachable":
+                return "network unreachable";
+            case "crashed":
+                return "agent crashed";
+        }
+    }
+
+    stopReasonLabel(): string | null {
+        const reason = this.task?.stopReason;
+        if (this.task?.status !== "STOPPED" || reason?.kind !== "budgetExceeded") {
+            return null;
+        }
+        return `${this.budgetLabel(reason.budget)} budget of ${reason.limitSecs}s ran out`;
+    }
+
     canStart(): boolean {
         return (
             !!this.task &&
@@ -306,6 +357,17 @@ export class TaskViewComponent {
             window.addEventListener("mouseup", handleUp);
         });
     }
+    private budgetLabel(budget: Budget): string {
+        switch (budget) {
+            case "runtime":
+                return "runtime";
+            case "working":
+                return "working";
+            case "awaitingApproval":
+                return "approval wait";
+        }
+    }
+
     private describeError(error: unknown, fallback: string): string {
         if (typeof error === "string") {
             return error;